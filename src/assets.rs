@@ -0,0 +1,187 @@
+use std::{
+    env,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::de::DeserializeOwned;
+
+// Where the game's assets are found when no directory is given
+pub const ASSETS_DIRECTORY: &str = "assets";
+
+// Which kind of asset failed to load, named in the error message
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetKind {
+    Level,
+    Movement,
+    Prefab,
+}
+
+impl fmt::Display for AssetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AssetKind::Level => "level",
+            AssetKind::Movement => "movement",
+            AssetKind::Prefab => "prefab",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug)]
+pub enum AssetError {
+    ReadDirectory {
+        kind: AssetKind,
+        path: PathBuf,
+        source: io::Error,
+    },
+    ReadFile {
+        kind: AssetKind,
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        kind: AssetKind,
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    InvalidField {
+        kind: AssetKind,
+        path: PathBuf,
+        field: String,
+        message: String,
+    },
+    // A directory lacks a file it has to have, e.g. any level at all
+    Missing {
+        kind: AssetKind,
+        path: PathBuf,
+        what: String,
+    },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetError::ReadDirectory { kind, path, source } => {
+                write!(
+                    f,
+                    "failed to read {} directory {}: {}",
+                    kind,
+                    path.display(),
+                    source
+                )
+            }
+            AssetError::ReadFile { kind, path, source } => {
+                write!(
+                    f,
+                    "failed to read {} file {}: {}",
+                    kind,
+                    path.display(),
+                    source
+                )
+            }
+            AssetError::Parse { kind, path, source } => {
+                write!(
+                    f,
+                    "failed to parse {} file {}: {}",
+                    kind,
+                    path.display(),
+                    source
+                )
+            }
+            AssetError::InvalidField {
+                kind,
+                path,
+                field,
+                message,
+            } => write!(
+                f,
+                "invalid {} file {}: field `{}` {}",
+                kind,
+                path.display(),
+                field,
+                message
+            ),
+            AssetError::Missing { path, what, .. } => {
+                write!(f, "no {} found in {}", what, path.display())
+            }
+        }
+    }
+}
+
+impl Error for AssetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AssetError::ReadDirectory { source, .. } | AssetError::ReadFile { source, .. } => {
+                Some(source)
+            }
+            AssetError::Parse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// The directory given on the command line, or else the first `assets`
+// directory next to the executable or in the working directory. If there is
+// none, the working directory's is returned so loading reports it as missing.
+pub fn assets_directory(argument: Option<PathBuf>) -> PathBuf {
+    if let Some(directory) = argument {
+        return directory;
+    }
+
+    let beside_executable = env::current_exe().ok().and_then(|executable| {
+        executable
+            .parent()
+            .map(|parent| parent.join(ASSETS_DIRECTORY))
+    });
+
+    beside_executable
+        .filter(|directory| directory.is_dir())
+        .unwrap_or_else(|| Path::new(ASSETS_DIRECTORY).to_path_buf())
+}
+
+pub(crate) fn read_file(kind: AssetKind, path: &Path) -> Result<String, AssetError> {
+    fs::read_to_string(path).map_err(|source| AssetError::ReadFile {
+        kind,
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+// `path` is only used to name the file in errors
+pub(crate) fn parse_json<T: DeserializeOwned>(
+    kind: AssetKind,
+    contents: &str,
+    path: &Path,
+) -> Result<T, AssetError> {
+    serde_json::from_str(contents).map_err(|source| AssetError::Parse {
+        kind,
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+// Every `.json` file in `directory`, ordered by file name
+pub(crate) fn json_files(kind: AssetKind, directory: &Path) -> Result<Vec<PathBuf>, AssetError> {
+    let read_dir_error = |source| AssetError::ReadDirectory {
+        kind,
+        path: directory.to_path_buf(),
+        source,
+    };
+
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(directory).map_err(read_dir_error)? {
+        let path = entry.map_err(read_dir_error)?.path();
+
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    Ok(paths)
+}
@@ -1,5 +1,10 @@
 use once_cell::sync::Lazy;
-use sfml::{audio::SoundBuffer, SfBox};
+use sfml::{
+    audio::{Sound, SoundBuffer},
+    SfBox,
+};
+
+use crate::events::{EventSubscriber, GameEvent};
 
 const DEATH_SFX_DATA: &'static [u8] = include_bytes!("../assets/audio/Explosion 12.ogg");
 pub static mut DEATH_SFX: Lazy<SfBox<SoundBuffer>> =
@@ -12,3 +17,40 @@ pub static mut LEVEL_COMPLETE_SFX: Lazy<SfBox<SoundBuffer>> =
 const JUMP_SFX_DATA: &'static [u8] = include_bytes!("../assets/audio/Jump 47.ogg");
 pub static mut JUMP_SFX: Lazy<SfBox<SoundBuffer>> =
     Lazy::new(|| SoundBuffer::from_memory(JUMP_SFX_DATA).unwrap());
+
+// Plays a sound for the events that have one
+pub struct SoundEffects {
+    death: Sound<'static>,
+    level_complete: Sound<'static>,
+    jump: Sound<'static>,
+}
+
+impl SoundEffects {
+    // The buffers are only ever read, so sharing them is sound
+    pub fn new() -> Self {
+        Self {
+            death: Sound::with_buffer(unsafe { &*std::ptr::addr_of!(DEATH_SFX) }),
+            level_complete: Sound::with_buffer(unsafe { &*std::ptr::addr_of!(LEVEL_COMPLETE_SFX) }),
+            jump: Sound::with_buffer(unsafe { &*std::ptr::addr_of!(JUMP_SFX) }),
+        }
+    }
+}
+
+impl EventSubscriber for SoundEffects {
+    fn on_event(&mut self, event: GameEvent) {
+        match event {
+            GameEvent::PlayerJumped | GameEvent::AirJumped | GameEvent::WallJumped => {
+                self.jump.play()
+            }
+            GameEvent::PlayerDied => self.death.play(),
+            GameEvent::LevelCompleted => self.level_complete.play(),
+            _ => {}
+        }
+    }
+}
+
+impl Default for SoundEffects {
+    fn default() -> Self {
+        Self::new()
+    }
+}
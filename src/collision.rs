@@ -0,0 +1,139 @@
+use sfml::system::Vector2;
+
+// How many times a move is allowed to slide along a surface and carry on
+pub const MAX_SWEEP_ITERATIONS: usize = 4;
+// How many passes are made pushing a box out of everything it overlaps
+pub const MAX_RESOLVE_ITERATIONS: usize = 4;
+// Boxes closer than this are considered to be touching
+pub const CONTACT_EPSILON: f32 = 0.01;
+
+// The sides of a box that are touching something
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Contacts {
+    pub floor: bool,
+    pub ceiling: bool,
+    pub left_wall: bool,
+    pub right_wall: bool,
+}
+
+impl Contacts {
+    // Records a contact from the normal of the surface that was touched
+    pub fn add(&mut self, normal: Vector2<f32>) {
+        if normal.y < 0.0 {
+            self.floor = true;
+        } else if normal.y > 0.0 {
+            self.ceiling = true;
+        }
+        if normal.x > 0.0 {
+            self.left_wall = true;
+        } else if normal.x < 0.0 {
+            self.right_wall = true;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SweepHit {
+    // Fraction of the move, from 0.0 to 1.0, at which the boxes first touch
+    pub time: f32,
+    // Points away from the surface that was hit, e.g. (0, -1) for a floor
+    pub normal: Vector2<f32>,
+    // Where the moving box is at `time`, flush against the surface
+    pub position: Vector2<f32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Penetration {
+    // How far the box has to move along `normal` to stop overlapping
+    pub depth: f32,
+    // Points out of the surface the box should be pushed through
+    pub normal: Vector2<f32>,
+}
+
+// Sweeps a box centered on `start` along `delta` against a static box and
+// returns the first contact, if there is one during this move. Boxes that only
+// graze each other's edges, or that already overlap at `start`, don't count.
+pub fn sweep_aabb(
+    start: Vector2<f32>,
+    delta: Vector2<f32>,
+    half_size: Vector2<f32>,
+    target_position: Vector2<f32>,
+    target_half_size: Vector2<f32>,
+) -> Option<SweepHit> {
+    // Shrink the moving box to a point by growing the target by its size
+    let extent = half_size + target_half_size;
+    let min = target_position - extent;
+    let max = target_position + extent;
+
+    let (entry_x, exit_x) = slab(start.x, delta.x, min.x, max.x)?;
+    let (entry_y, exit_y) = slab(start.y, delta.y, min.y, max.y)?;
+
+    let entry = entry_x.max(entry_y);
+    let exit = exit_x.min(exit_y);
+
+    if entry >= exit || !(0.0..=1.0).contains(&entry) {
+        return None;
+    }
+
+    let mut position = start + delta * entry;
+
+    // On an exact corner hit prefer the floor or ceiling, so the player lands
+    let normal = if entry_y >= entry_x {
+        if delta.y > 0.0 {
+            position.y = min.y;
+            Vector2::new(0.0, -1.0)
+        } else {
+            position.y = max.y;
+            Vector2::new(0.0, 1.0)
+        }
+    } else if delta.x > 0.0 {
+        position.x = min.x;
+        Vector2::new(-1.0, 0.0)
+    } else {
+        position.x = max.x;
+        Vector2::new(1.0, 0.0)
+    };
+
+    Some(SweepHit {
+        time: entry,
+        normal,
+        position,
+    })
+}
+
+// Entry and exit times of a point moving along one axis through [min, max]
+fn slab(start: f32, delta: f32, min: f32, max: f32) -> Option<(f32, f32)> {
+    if delta == 0.0 {
+        // Never enters unless it is already strictly inside
+        return if start > min && start < max {
+            Some((f32::NEG_INFINITY, f32::INFINITY))
+        } else {
+            None
+        };
+    }
+
+    let t_min = (min - start) / delta;
+    let t_max = (max - start) / delta;
+
+    Some((t_min.min(t_max), t_min.max(t_max)))
+}
+
+// The normal of the target's face a box is resting against, if the two are
+// touching along an edge without overlapping
+pub fn touching_normal(
+    position: Vector2<f32>,
+    half_size: Vector2<f32>,
+    target_position: Vector2<f32>,
+    target_half_size: Vector2<f32>,
+) -> Option<Vector2<f32>> {
+    let delta = position - target_position;
+    let gap = Vector2::new(delta.x.abs(), delta.y.abs()) - (half_size + target_half_size);
+
+    if gap.x.abs() <= CONTACT_EPSILON && gap.y < -CONTACT_EPSILON {
+        Some(Vector2::new(delta.x.signum(), 0.0))
+    } else if gap.y.abs() <= CONTACT_EPSILON && gap.x < -CONTACT_EPSILON {
+        Some(Vector2::new(0.0, delta.y.signum()))
+    } else {
+        None
+    }
+}
@@ -1,8 +1,13 @@
 use std::any::Any;
 
+use serde::Deserialize;
 use sfml::system::Vector2;
 
-pub trait Component: Any {
+use crate::collision::Penetration;
+
+// Components are plain data owned by a single pool, so they must be Send for
+// a World to be moved to another thread
+pub trait Component: Any + Send {
     fn as_any(&self) -> &dyn Any;
 
     fn as_any_mut(&mut self) -> &mut dyn Any;
@@ -11,6 +16,7 @@ pub trait Component: Any {
 
     fn set_active(&mut self, active: bool);
 
+    // Puts the component back the way it was when first added
     fn reset(&mut self);
 }
 
@@ -44,29 +50,102 @@ impl CTransform {
         }
     }
 
-    pub fn update(&mut self) {
+    pub fn with_position(mut self, position: Vector2<f32>) -> Self {
+        self.position = position;
+        self.prev_position = position;
+        self
+    }
+
+    pub fn with_size(mut self, size: Vector2<f32>) -> Self {
+        self.size = size;
+        self.half_size = size / 2.0;
+        self
+    }
+
+    pub fn with_max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed;
+        self
+    }
+
+    pub fn update(&mut self, dt: f32) {
         self.prev_position = self.position;
 
-        self.velocity += self.acceleration;
-        self.position += self.velocity;
+        self.velocity += self.acceleration * dt;
+        self.position += self.velocity * dt;
     }
 
+    // How far another box overlaps this one on each axis; positive on both
+    // axes means they intersect, zero means they are exactly touching
     pub fn get_overlap(
-        &mut self,
+        &self,
         other_position: Vector2<f32>,
         other_half_size: Vector2<f32>,
-    ) -> Vector2<i32> {
-        let delta: Vector2<i32> = Vector2::new(
-            ((self.position.x - other_position.x) as i32).abs(),
-            ((self.position.y - other_position.y) as i32).abs(),
+    ) -> Vector2<f32> {
+        let delta = Vector2::new(
+            (self.position.x - other_position.x).abs(),
+            (self.position.y - other_position.y).abs(),
         );
 
-        let overlap: Vector2<i32> = Vector2::new(
-            (self.half_size.x + other_half_size.x) as i32 - delta.x,
-            (self.half_size.y + other_half_size.y) as i32 - delta.y,
-        );
+        self.half_size + other_half_size - delta
+    }
+
+    // The smallest push that moves another box out of this one. The axis it
+    // was not already overlapping on last frame is the one it entered along.
+    pub fn get_penetration(
+        &self,
+        other_position: Vector2<f32>,
+        other_prev_position: Vector2<f32>,
+        other_half_size: Vector2<f32>,
+    ) -> Option<Penetration> {
+        let overlap = self.get_overlap(other_position, other_half_size);
 
-        return overlap;
+        if overlap.x <= 0.0 || overlap.y <= 0.0 {
+            return None;
+        }
+
+        let prev_overlap = self.get_overlap(other_prev_position, other_half_size);
+
+        let push_vertically = if prev_overlap.x > 0.0 {
+            true
+        } else if prev_overlap.y > 0.0 {
+            false
+        } else {
+            overlap.x > overlap.y
+        };
+
+        let penetration = if push_vertically {
+            Penetration {
+                depth: overlap.y,
+                normal: Vector2::new(
+                    0.0,
+                    if self.position.y < other_position.y {
+                        1.0
+                    } else {
+                        -1.0
+                    },
+                ),
+            }
+        } else {
+            Penetration {
+                depth: overlap.x,
+                normal: Vector2::new(
+                    if self.position.x < other_position.x {
+                        1.0
+                    } else {
+                        -1.0
+                    },
+                    0.0,
+                ),
+            }
+        };
+
+        Some(penetration)
+    }
+}
+
+impl Default for CTransform {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -88,21 +167,10 @@ impl Component for CTransform {
     }
 
     fn reset(&mut self) {
-        self.active = true;
-        self.position.x = 0.0;
-        self.position.y = 0.0;
-        self.prev_position.x = 0.0;
-        self.prev_position.y = 0.0;
-        self.velocity.x = 0.0;
-        self.velocity.y = 0.0;
-        self.acceleration.x = 0.0;
-        self.acceleration.y = 0.0;
-        self.size.x = 0.0;
-        self.size.y = 0.0;
-        self.half_size.x = 0.0;
-        self.half_size.y = 0.0;
-        self.max_speed = 0.0;
-        self.scale = 1.0;
+        *self = Self {
+            active: true,
+            ..Self::new()
+        };
     }
 }
 
@@ -131,6 +199,12 @@ impl CLifetime {
     }
 }
 
+impl Default for CLifetime {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
 impl Component for CLifetime {
     fn as_any(&self) -> &dyn Any {
         self
@@ -148,10 +222,10 @@ impl Component for CLifetime {
         self.active = active;
     }
 
+    // Keeps the duration it was created with and starts counting down again
     fn reset(&mut self) {
         self.active = true;
-        self.lifetime_duration = 0.0;
-        self.lifetime_timer = 0.0;
+        self.lifetime_timer = self.lifetime_duration;
     }
 }
 
@@ -162,6 +236,21 @@ pub struct ButtonState {
     pub released: bool,
 }
 
+impl ButtonState {
+    pub fn press(&mut self) {
+        // Ignore key repeat
+        if !self.held {
+            self.pressed = true;
+            self.held = true;
+        }
+    }
+
+    pub fn release(&mut self) {
+        self.held = false;
+        self.released = true;
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct CInput {
     active: bool,
@@ -170,6 +259,7 @@ pub struct CInput {
     pub left: ButtonState,
     pub right: ButtonState,
     pub space: ButtonState,
+    pub dash: ButtonState,
 }
 
 impl CInput {
@@ -201,8 +291,34 @@ impl CInput {
                 held: false,
                 released: false,
             },
+            dash: ButtonState {
+                pressed: false,
+                held: false,
+                released: false,
+            },
         }
     }
+
+    pub fn clear_transitions(&mut self) {
+        self.up.pressed = false;
+        self.up.released = false;
+        self.down.pressed = false;
+        self.down.released = false;
+        self.left.pressed = false;
+        self.left.released = false;
+        self.right.pressed = false;
+        self.right.released = false;
+        self.space.pressed = false;
+        self.space.released = false;
+        self.dash.pressed = false;
+        self.dash.released = false;
+    }
+}
+
+impl Default for CInput {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Component for CInput {
@@ -239,6 +355,94 @@ impl Component for CInput {
         self.space.pressed = false;
         self.space.held = false;
         self.space.released = false;
+        self.dash.pressed = false;
+        self.dash.held = false;
+        self.dash.released = false;
+    }
+}
+
+// Drawn by the presentation layer as a window of its own, filled with `color`
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CRender {
+    #[serde(skip)]
+    active: bool,
+    pub color: [u8; 3],
+    pub title: String,
+}
+
+impl CRender {
+    pub fn new(color: [u8; 3], title: &str) -> Self {
+        Self {
+            active: false,
+            color,
+            title: title.to_string(),
+        }
+    }
+}
+
+impl Component for CRender {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn is_active(&self) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    fn reset(&mut self) {
+        self.active = true;
+    }
+}
+
+// Collected when the player touches it, granting its abilities for the rest
+// of the level
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CPickup {
+    #[serde(skip)]
+    active: bool,
+    // Extra jumps the player can make before landing
+    #[serde(default)]
+    pub air_jumps: u32,
+}
+
+impl CPickup {
+    pub fn new(air_jumps: u32) -> Self {
+        Self {
+            active: false,
+            air_jumps,
+        }
+    }
+}
+
+impl Component for CPickup {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn is_active(&self) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    fn reset(&mut self) {
+        self.active = true;
     }
 }
 
@@ -253,6 +457,12 @@ impl CAIBasic {
     }
 }
 
+impl Default for CAIBasic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Component for CAIBasic {
     fn as_any(&self) -> &dyn Any {
         self
@@ -293,6 +503,12 @@ impl CHealth {
     }
 }
 
+impl Default for CHealth {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
 impl Component for CHealth {
     fn as_any(&self) -> &dyn Any {
         self
@@ -312,8 +528,7 @@ impl Component for CHealth {
 
     fn reset(&mut self) {
         self.active = true;
-        self.health = 0.0;
-        self.max_health = 0.0;
+        self.health = self.max_health;
     }
 }
 
@@ -332,6 +547,12 @@ impl CDamage {
     }
 }
 
+impl Default for CDamage {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
 impl Component for CDamage {
     fn as_any(&self) -> &dyn Any {
         self
@@ -351,6 +572,5 @@ impl Component for CDamage {
 
     fn reset(&mut self) {
         self.active = true;
-        self.damage = 0.0;
     }
 }
@@ -1,8 +1,11 @@
+pub mod command_buffer;
 pub mod entity;
 pub mod entity_manager;
 pub mod entity_memory_pool;
 
-#[derive(Clone, Eq, PartialEq, Hash, Debug, Copy)]
+use serde::Deserialize;
+
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Copy, Deserialize)]
 pub enum EntityTag {
     None,
     Player,
@@ -10,4 +13,6 @@ pub enum EntityTag {
     Enemy,
     Platform,
     Goal,
+    Hazard,
+    Pickup,
 }
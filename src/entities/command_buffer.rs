@@ -0,0 +1,71 @@
+use crate::components::Component;
+
+use super::{entity::Entity, entity_memory_pool::EntityMemoryPool, EntityTag};
+
+enum Command {
+    Spawn(Entity, EntityTag),
+    Destroy(Entity),
+    // Adding or removing a component, with its type captured in the closure
+    Apply(Box<dyn FnOnce(&mut EntityMemoryPool) + Send>),
+}
+
+// Structural changes queued up during a frame and applied together, in the
+// order they were made, when the buffer is flushed
+#[derive(Default)]
+pub struct CommandBuffer {
+    commands: Vec<Command>,
+}
+
+impl CommandBuffer {
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+        }
+    }
+
+    // The entity must already be reserved in the pool
+    pub fn spawn(&mut self, entity: Entity, tag: EntityTag) {
+        self.commands.push(Command::Spawn(entity, tag));
+    }
+
+    pub fn destroy(&mut self, entity: Entity) {
+        self.commands.push(Command::Destroy(entity));
+    }
+
+    pub fn add_component<T: Component>(&mut self, entity: Entity, component: T) {
+        self.commands
+            .push(Command::Apply(Box::new(move |entity_memory_pool| {
+                entity_memory_pool.add_component(entity, component);
+            })));
+    }
+
+    pub fn remove_component<T: Component>(&mut self, entity: Entity) {
+        self.commands
+            .push(Command::Apply(Box::new(move |entity_memory_pool| {
+                entity_memory_pool.remove_component::<T>(entity);
+            })));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    // Applies every queued command to the pool and returns the entities that
+    // were spawned, including any destroyed again later in the same flush
+    pub fn flush(&mut self, entity_memory_pool: &mut EntityMemoryPool) -> Vec<(Entity, EntityTag)> {
+        let mut spawned = Vec::new();
+
+        for command in self.commands.drain(..) {
+            match command {
+                Command::Spawn(entity, tag) => {
+                    entity_memory_pool.activate(entity);
+                    spawned.push((entity, tag));
+                }
+                Command::Destroy(entity) => entity_memory_pool.destroy_entity(entity),
+                Command::Apply(apply) => apply(entity_memory_pool),
+            }
+        }
+
+        spawned
+    }
+}
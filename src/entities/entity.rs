@@ -1,44 +1,67 @@
 use crate::components::Component;
 
-use super::entity_memory_pool::EntityMemoryPool;
+use super::{entity_manager::EntityManager, entity_memory_pool::EntityMemoryPool};
 
-#[derive(Clone, Copy)]
+// A handle to a slot in the EntityMemoryPool. The generation is bumped each
+// time the slot is freed, so handles to a destroyed entity stop resolving
+// instead of aliasing whatever reuses the slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Entity {
     id: usize,
+    generation: u32,
 }
 
 impl Entity {
-    pub fn new(id: usize) -> Self {
-        Self { id }
+    pub fn new(id: usize, generation: u32) -> Self {
+        Self { id, generation }
     }
 
     pub fn id(&self) -> usize {
         return self.id;
     }
 
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn is_alive(&self, entity_memory_pool: &EntityMemoryPool) -> bool {
+        entity_memory_pool.is_alive(*self)
+    }
+
     pub fn get_component_mut<'a, T: Component>(
         &'a mut self,
         entity_memory_pool: &'a mut EntityMemoryPool,
     ) -> Option<&'a mut T> {
-        return entity_memory_pool.get_component_mut::<T>(self.id);
+        return entity_memory_pool.get_component_mut::<T>(*self);
     }
 
     pub fn get_component<'a, T: Component>(
         &'a self,
         entity_memory_pool: &'a EntityMemoryPool,
     ) -> Option<&'a T> {
-        return entity_memory_pool.get_component::<T>(self.id);
+        return entity_memory_pool.get_component::<T>(*self);
     }
 
-    // pub fn has_component<T: Component>(&self, entity_memory_pool: &EntityMemoryPool) -> bool {
-    //     return entity_memory_pool.has_component::<T>(self.id);
-    // }
+    pub fn has_component<T: Component>(&self, entity_memory_pool: &EntityMemoryPool) -> bool {
+        entity_memory_pool.has_component::<T>(*self)
+    }
+
+    // Queued, so the entity is removed on the manager's next update
+    pub fn destroy(&mut self, entity_manager: &mut EntityManager) {
+        entity_manager.destroy_entity(*self);
+    }
 
-    pub fn destroy(&mut self, entity_memory_pool: &mut EntityMemoryPool) {
-        entity_memory_pool.destroy_entity(self.id);
+    // Queued like destroy, so the component changes on the manager's next
+    // update
+    pub fn add_component<T: Component>(
+        &mut self,
+        entity_manager: &mut EntityManager,
+        component: T,
+    ) {
+        entity_manager.add_component(*self, component);
     }
 
-    pub fn add_component<T: Component>(&mut self, entity_memory_pool: &mut EntityMemoryPool) {
-        entity_memory_pool.add_component::<T>(self.id);
+    pub fn remove_component<T: Component>(&mut self, entity_manager: &mut EntityManager) {
+        entity_manager.remove_component::<T>(*self);
     }
 }
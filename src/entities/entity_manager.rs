@@ -1,14 +1,24 @@
 use std::collections::HashMap;
 
-use super::{entity::Entity, entity_memory_pool::EntityMemoryPool, EntityTag};
+use crate::components::Component;
+
+use super::{
+    command_buffer::CommandBuffer,
+    entity::Entity,
+    entity_memory_pool::{EntityMemoryPool, PoolFullError},
+    EntityTag,
+};
 
 type EntityVec = Vec<Entity>;
 type EntityMap = HashMap<EntityTag, EntityVec>;
 
+// Spawning, destroying and adding or removing components through the manager
+// is deferred until update(), so systems iterating the entity lists or
+// querying the pool never see an entity half added or half removed
 pub struct EntityManager {
     pub entities: EntityVec,
     pub entity_map: EntityMap,
-    pub entities_to_add: Vec<(Entity, EntityTag)>,
+    pub commands: CommandBuffer,
 }
 
 impl EntityManager {
@@ -19,11 +29,13 @@ impl EntityManager {
         entity_map.insert(EntityTag::Enemy, Vec::with_capacity(10000));
         entity_map.insert(EntityTag::Platform, Vec::with_capacity(10000));
         entity_map.insert(EntityTag::Goal, Vec::with_capacity(1));
+        entity_map.insert(EntityTag::Hazard, Vec::with_capacity(1000));
+        entity_map.insert(EntityTag::Pickup, Vec::with_capacity(1000));
 
         Self {
             entities: Vec::with_capacity(20000),
             entity_map,
-            entities_to_add: Vec::with_capacity(10000),
+            commands: CommandBuffer::new(),
         }
     }
 
@@ -32,15 +44,38 @@ impl EntityManager {
         tag: EntityTag,
         entity_memory_pool: &mut EntityMemoryPool,
     ) -> Entity {
-        // Create a new entity
-        let entity = entity_memory_pool.add_entity(tag);
-        // Add the entity to the entities-to-add list
-        self.entities_to_add.push((entity, tag));
+        // Reserve a slot, so the handle can be used to queue its components
+        let entity = entity_memory_pool.reserve_entity(tag);
+        // Spawn it for real on the next update
+        self.commands.spawn(entity, tag);
 
         // Return the new entity
         return entity;
     }
 
+    pub fn try_add_entity(
+        &mut self,
+        tag: EntityTag,
+        entity_memory_pool: &mut EntityMemoryPool,
+    ) -> Result<Entity, PoolFullError> {
+        let entity = entity_memory_pool.try_reserve_entity(tag)?;
+        self.commands.spawn(entity, tag);
+
+        Ok(entity)
+    }
+
+    pub fn destroy_entity(&mut self, entity: Entity) {
+        self.commands.destroy(entity);
+    }
+
+    pub fn add_component<T: Component>(&mut self, entity: Entity, component: T) {
+        self.commands.add_component(entity, component);
+    }
+
+    pub fn remove_component<T: Component>(&mut self, entity: Entity) {
+        self.commands.remove_component::<T>(entity);
+    }
+
     pub fn get_all_entities(&self) -> &EntityVec {
         return &self.entities;
     }
@@ -75,8 +110,8 @@ impl EntityManager {
     }
 
     pub fn update(&mut self, entity_memory_pool: &mut EntityMemoryPool) {
-        // For each entity in the entities-to-add list
-        for entity_and_tag in self.entities_to_add.drain(..) {
+        // For each entity spawned since the last update
+        for entity_and_tag in self.commands.flush(entity_memory_pool) {
             // Add the entity to the main entity list
             self.entities.push(entity_and_tag.0);
 
@@ -89,12 +124,12 @@ impl EntityManager {
 
         // Remove all entities that are not alive from the main entity list
         self.entities
-            .retain(|entity| entity_memory_pool.is_active(entity.id()));
+            .retain(|entity| entity_memory_pool.is_alive(*entity));
 
         // For every pair in the entity map
         for (_, entities) in self.entity_map.iter_mut() {
             // Remove all entities that are not alive
-            entities.retain(|entity| entity_memory_pool.is_active(entity.id()));
+            entities.retain(|entity| entity_memory_pool.is_alive(*entity));
         }
     }
 }
@@ -1,141 +1,438 @@
-use std::{any::TypeId, sync::Mutex};
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    error::Error,
+    fmt,
+};
 
-use once_cell::sync::Lazy;
-use sfml::system::Vector2;
-
-use crate::components::{CInput, CLifetime, CTransform, Component};
+use crate::components::{
+    CAIBasic, CDamage, CHealth, CInput, CLifetime, CPickup, CRender, CTransform, Component,
+};
 
 use super::{entity::Entity, EntityTag};
 
-type EntityComponentVectorTuple = (Vec<CTransform>, Vec<CLifetime>, Vec<CInput>);
+// One slot per entity for a single component type. Every registered type gets
+// a Vec<T>, kept behind this trait so the pool can hold them all in one map.
+trait ComponentStorage: Send {
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    fn add_slots(&mut self, count: usize);
+}
+
+impl<T: Component + Default> ComponentStorage for Vec<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn add_slots(&mut self, count: usize) {
+        self.extend((0..count).map(|_| T::default()));
+    }
+}
 
-pub static POOL: Lazy<Mutex<EntityMemoryPool>> =
-    Lazy::new(|| Mutex::new(EntityMemoryPool::new(20000)));
+// One bit per registered component type, set for the components an entity has
+pub type ComponentMask = u64;
+
+const MAX_COMPONENT_TYPES: u32 = ComponentMask::BITS;
+
+struct ComponentColumn {
+    mask: ComponentMask,
+    storage: Box<dyn ComponentStorage>,
+}
+
+// Returned when every slot in a fixed-size pool is in use
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolFullError {
+    pub capacity: usize,
+}
+
+impl fmt::Display for PoolFullError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "entity pool is full ({} entities)", self.capacity)
+    }
+}
+
+impl Error for PoolFullError {}
 
 pub struct EntityMemoryPool {
     entity_count: usize,
-    pool: EntityComponentVectorTuple,
+    components: HashMap<TypeId, ComponentColumn>,
     tags: Vec<EntityTag>,
+    masks: Vec<ComponentMask>,
     active: Vec<bool>,
+    // Handed out by reserve_entity but not yet activated: components can be
+    // added through the handle, but queries don't see the entity yet
+    reserved: Vec<bool>,
+    generations: Vec<u32>,
+    // Inactive slot ids, reused most recently freed first
+    free_ids: Vec<usize>,
+    // One past the highest slot ever handed out, so queries can stop there
+    // instead of scanning every slot in the pool
+    used_slots: usize,
+    growable: bool,
 }
 
 impl EntityMemoryPool {
     pub fn new(entity_count: usize) -> Self {
-        let mut pool = (
-            Vec::<CTransform>::with_capacity(entity_count),
-            Vec::<CLifetime>::with_capacity(entity_count),
-            Vec::<CInput>::with_capacity(entity_count),
-        );
+        let mut entity_memory_pool = Self {
+            entity_count: 0,
+            components: HashMap::new(),
+            tags: Vec::with_capacity(entity_count),
+            masks: Vec::with_capacity(entity_count),
+            active: Vec::with_capacity(entity_count),
+            reserved: Vec::with_capacity(entity_count),
+            generations: Vec::with_capacity(entity_count),
+            free_ids: Vec::with_capacity(entity_count),
+            used_slots: 0,
+            growable: false,
+        };
 
-        let mut tags = Vec::<EntityTag>::with_capacity(entity_count);
-        let mut active = Vec::<bool>::with_capacity(entity_count);
+        entity_memory_pool.add_slots(entity_count);
 
-        for _ in 0..entity_count {
-            pool.0.push(CTransform::new());
-            pool.1.push(CLifetime::new(0.0));
-            pool.2.push(CInput::new());
+        entity_memory_pool.register_component::<CTransform>();
+        entity_memory_pool.register_component::<CLifetime>();
+        entity_memory_pool.register_component::<CInput>();
+        entity_memory_pool.register_component::<CRender>();
+        entity_memory_pool.register_component::<CPickup>();
+        entity_memory_pool.register_component::<CAIBasic>();
+        entity_memory_pool.register_component::<CHealth>();
+        entity_memory_pool.register_component::<CDamage>();
 
-            tags.push(EntityTag::None);
-            active.push(false);
-        }
+        entity_memory_pool
+    }
 
+    // A pool that doubles its slot count instead of failing when it runs out
+    pub fn growable(entity_count: usize) -> Self {
         Self {
-            entity_count,
-            pool,
-            tags,
-            active,
+            growable: true,
+            ..Self::new(entity_count)
         }
     }
 
-    pub fn get_component<T: Component>(&self, id: usize) -> Option<&T> {
-        let type_id = TypeId::of::<T>();
+    // Gives every entity a slot for T. The built-in components are registered
+    // by new(); registering a type twice keeps the existing storage.
+    pub fn register_component<T: Component + Default>(&mut self) {
+        if self.is_registered::<T>() {
+            return;
+        }
 
-        match type_id {
-            x if x == TypeId::of::<CTransform>() => {
-                Some(&self.pool.0[id].as_any().downcast_ref().unwrap() as &T)
-            }
-            x if x == TypeId::of::<CLifetime>() => {
-                Some(&self.pool.1[id].as_any().downcast_ref().unwrap() as &T)
-            }
-            x if x == TypeId::of::<CInput>() => {
-                Some(&self.pool.2[id].as_any().downcast_ref().unwrap() as &T)
-            }
-            _ => None,
+        let bit = self.components.len() as u32;
+        assert!(
+            bit < MAX_COMPONENT_TYPES,
+            "no more than {} component types can be registered",
+            MAX_COMPONENT_TYPES
+        );
+
+        let mut storage = Vec::<T>::with_capacity(self.entity_count);
+        storage.add_slots(self.entity_count);
+
+        self.components.insert(
+            TypeId::of::<T>(),
+            ComponentColumn {
+                mask: 1 << bit,
+                storage: Box::new(storage),
+            },
+        );
+    }
+
+    pub fn is_registered<T: Component>(&self) -> bool {
+        self.components.contains_key(&TypeId::of::<T>())
+    }
+
+    // The bit T sets in an entity's mask, if T is registered
+    pub fn component_mask<T: Component>(&self) -> Option<ComponentMask> {
+        self.components
+            .get(&TypeId::of::<T>())
+            .map(|column| column.mask)
+    }
+
+    // Every component the entity has; empty for a stale handle
+    pub fn get_mask(&self, entity: Entity) -> ComponentMask {
+        if !self.exists(entity) {
+            return 0;
         }
+
+        self.masks[entity.id()]
     }
 
-    pub fn get_component_mut<T: Component>(&mut self, id: usize) -> Option<&mut T> {
-        let type_id = TypeId::of::<T>();
+    pub fn has_component<T: Component>(&self, entity: Entity) -> bool {
+        match self.component_mask::<T>() {
+            Some(mask) => self.get_mask(entity) & mask != 0,
+            None => false,
+        }
+    }
 
-        match type_id {
-            x if x == TypeId::of::<CTransform>() => {
-                Some(self.pool.0[id].as_any_mut().downcast_mut().unwrap() as &mut T)
-            }
-            x if x == TypeId::of::<CLifetime>() => {
-                Some(self.pool.1[id].as_any_mut().downcast_mut().unwrap() as &mut T)
-            }
-            x if x == TypeId::of::<CInput>() => {
-                Some(self.pool.2[id].as_any_mut().downcast_mut().unwrap() as &mut T)
-            }
-            _ => None,
+    pub fn get_component<T: Component>(&self, entity: Entity) -> Option<&T> {
+        if !self.has_component::<T>(entity) {
+            return None;
         }
+
+        self.storage::<T>()?.get(entity.id())
+    }
+
+    pub fn get_component_mut<T: Component>(&mut self, entity: Entity) -> Option<&mut T> {
+        if !self.has_component::<T>(entity) {
+            return None;
+        }
+
+        self.storage_mut::<T>()?.get_mut(entity.id())
+    }
+
+    // Every live entity that has a T, along with it, in slot order
+    pub fn query<T: Component>(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.query_filtered(None)
+    }
+
+    pub fn query_mut<T: Component>(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.query_filtered_mut(None)
+    }
+
+    // Like query(), limited to entities with the given tag
+    pub fn query_by_tag<T: Component>(&self, tag: EntityTag) -> impl Iterator<Item = (Entity, &T)> {
+        self.query_filtered(Some(tag))
     }
 
-    pub fn get_tag(&self, id: usize) -> &EntityTag {
-        &self.tags[id]
+    pub fn query_by_tag_mut<T: Component>(
+        &mut self,
+        tag: EntityTag,
+    ) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.query_filtered_mut(Some(tag))
+    }
+
+    // Every live entity that has both an A and a B, along with them
+    pub fn query2<A: Component, B: Component>(&self) -> impl Iterator<Item = (Entity, &A, &B)> {
+        self.query2_filtered(None)
+    }
+
+    pub fn query2_by_tag<A: Component, B: Component>(
+        &self,
+        tag: EntityTag,
+    ) -> impl Iterator<Item = (Entity, &A, &B)> {
+        self.query2_filtered(Some(tag))
+    }
+
+    pub fn get_tag(&self, entity: Entity) -> Option<&EntityTag> {
+        if !self.exists(entity) {
+            return None;
+        }
+
+        Some(&self.tags[entity.id()])
     }
 
     pub fn get_entity_count(&self) -> usize {
         self.entity_count
     }
 
-    pub fn add_entity(&mut self, tag: EntityTag) -> Entity {
-        let id = self.get_next_available_id().unwrap();
+    pub fn reserve_entity(&mut self, tag: EntityTag) -> Entity {
+        self.try_reserve_entity(tag)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    // Claims a slot for an entity that stays invisible to queries until it is
+    // activated, so it can be set up before systems see it
+    pub fn try_reserve_entity(&mut self, tag: EntityTag) -> Result<Entity, PoolFullError> {
+        if self.free_ids.is_empty() {
+            if !self.growable {
+                return Err(PoolFullError {
+                    capacity: self.entity_count,
+                });
+            }
+
+            self.add_slots(self.entity_count.max(1));
+        }
+
+        let id = self.free_ids.pop().unwrap();
+        self.used_slots = self.used_slots.max(id + 1);
         self.tags[id] = tag;
-        self.active[id] = true;
+        self.reserved[id] = true;
 
-        return Entity::new(id);
+        Ok(Entity::new(id, self.generations[id]))
     }
 
-    pub fn is_active(&self, id: usize) -> bool {
-        self.active[id]
+    // Makes a reserved entity live straight away. Like the other immediate
+    // changes below, it is only applied by CommandBuffer::flush, or to a
+    // reserved slot that no system can see yet.
+    pub(crate) fn activate(&mut self, entity: Entity) {
+        if !self.is_reserved(entity) {
+            return;
+        }
+
+        self.reserved[entity.id()] = false;
+        self.active[entity.id()] = true;
     }
 
-    pub fn add_component<T: Component>(&mut self, id: usize) {
-        let type_id = TypeId::of::<T>();
+    // Whether the handle still refers to the entity it was created for, and
+    // that entity has been activated
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        // A handle from a bigger pool may point past the end of this one
+        self.active.get(entity.id()).copied().unwrap_or(false)
+            && self.generations[entity.id()] == entity.generation()
+    }
 
-        match type_id {
-            x if x == TypeId::of::<CTransform>() => {
-                self.pool.0[id].reset();
-            }
-            x if x == TypeId::of::<CLifetime>() => {
-                self.pool.1[id].reset();
-            }
-            x if x == TypeId::of::<CInput>() => {
-                self.pool.2[id].reset();
-            }
-            _ => {}
+    pub fn is_reserved(&self, entity: Entity) -> bool {
+        self.reserved.get(entity.id()).copied().unwrap_or(false)
+            && self.generations[entity.id()] == entity.generation()
+    }
+
+    // Alive or reserved: the handle can still be used to reach its components
+    pub fn exists(&self, entity: Entity) -> bool {
+        self.is_alive(entity) || self.is_reserved(entity)
+    }
+
+    pub(crate) fn add_component<T: Component>(&mut self, entity: Entity, mut component: T) {
+        if !self.exists(entity) {
+            return;
+        }
+
+        let storage = self.storage_mut::<T>().unwrap_or_else(|| {
+            panic!(
+                "{} must be registered before it is added to an entity",
+                type_name::<T>()
+            )
+        });
+
+        component.set_active(true);
+        storage[entity.id()] = component;
+
+        let mask = self.component_mask::<T>().unwrap();
+        self.masks[entity.id()] |= mask;
+    }
+
+    pub(crate) fn remove_component<T: Component>(&mut self, entity: Entity) {
+        if !self.has_component::<T>(entity) {
+            return;
         }
+
+        self.storage_mut::<T>().unwrap()[entity.id()].set_active(false);
+
+        let mask = self.component_mask::<T>().unwrap();
+        self.masks[entity.id()] &= !mask;
     }
 
-    pub fn destroy_entity(&mut self, id: usize) {
+    // Frees the slot straight away; everything outside the crate destroys
+    // through the EntityManager, so systems never see an entity half removed
+    pub(crate) fn destroy_entity(&mut self, entity: Entity) {
+        if !self.exists(entity) {
+            return;
+        }
+
+        let id = entity.id();
         self.active[id] = false;
+        self.reserved[id] = false;
+        self.masks[id] = 0;
+        self.generations[id] = self.generations[id].wrapping_add(1);
+        self.free_ids.push(id);
     }
 
-    pub fn get_pool(&self) -> &EntityComponentVectorTuple {
-        &self.pool
+    fn query_filtered<T: Component>(
+        &self,
+        tag: Option<EntityTag>,
+    ) -> impl Iterator<Item = (Entity, &T)> {
+        let mask = self.component_mask::<T>().unwrap_or(0);
+
+        self.storage::<T>()
+            .into_iter()
+            .flatten()
+            .take(self.used_slots)
+            .enumerate()
+            .filter(move |(id, _)| self.matches(*id, mask, tag))
+            .map(|(id, component)| (Entity::new(id, self.generations[id]), component))
     }
 
-    pub fn get_pool_mut(&mut self) -> &mut EntityComponentVectorTuple {
-        &mut self.pool
+    fn query2_filtered<A: Component, B: Component>(
+        &self,
+        tag: Option<EntityTag>,
+    ) -> impl Iterator<Item = (Entity, &A, &B)> {
+        // Both bits, or none at all if either type isn't registered
+        let mask = match (self.component_mask::<A>(), self.component_mask::<B>()) {
+            (Some(a_mask), Some(b_mask)) => a_mask | b_mask,
+            _ => 0,
+        };
+
+        let a_storage = self.storage::<A>().into_iter().flatten();
+        let b_storage = self.storage::<B>().into_iter().flatten();
+
+        a_storage
+            .zip(b_storage)
+            .take(self.used_slots)
+            .enumerate()
+            .filter(move |(id, _)| self.matches(*id, mask, tag))
+            .map(|(id, (a, b))| (Entity::new(id, self.generations[id]), a, b))
     }
 
-    fn get_next_available_id(&self) -> Option<usize> {
-        for i in 0..self.entity_count {
-            if !self.active[i] {
-                return Some(i);
-            }
+    fn query_filtered_mut<T: Component>(
+        &mut self,
+        tag: Option<EntityTag>,
+    ) -> impl Iterator<Item = (Entity, &mut T)> {
+        let mask = self.component_mask::<T>().unwrap_or(0);
+
+        // Borrow the storage mutably and the bookkeeping separately, so the
+        // filter can read slot state while components are handed out
+        let storage = self.components.get_mut(&TypeId::of::<T>()).map(|column| {
+            column
+                .storage
+                .as_any_mut()
+                .downcast_mut::<Vec<T>>()
+                .unwrap()
+        });
+        let used_slots = self.used_slots;
+        let active = &self.active;
+        let masks = &self.masks;
+        let tags = &self.tags;
+        let generations = &self.generations;
+
+        storage
+            .into_iter()
+            .flatten()
+            .take(used_slots)
+            .enumerate()
+            .filter(move |(id, _)| {
+                active[*id] && masks[*id] & mask != 0 && tag.is_none_or(|tag| tags[*id] == tag)
+            })
+            .map(move |(id, component)| (Entity::new(id, generations[id]), component))
+    }
+
+    // Live, with every component in `mask` and the tag if one is given
+    fn matches(&self, id: usize, mask: ComponentMask, tag: Option<EntityTag>) -> bool {
+        self.active[id]
+            && mask != 0
+            && self.masks[id] & mask == mask
+            && tag.is_none_or(|tag| self.tags[id] == tag)
+    }
+
+    fn storage<T: Component>(&self) -> Option<&Vec<T>> {
+        self.components
+            .get(&TypeId::of::<T>())
+            .map(|column| column.storage.as_any().downcast_ref().unwrap())
+    }
+
+    fn storage_mut<T: Component>(&mut self) -> Option<&mut Vec<T>> {
+        self.components
+            .get_mut(&TypeId::of::<T>())
+            .map(|column| column.storage.as_any_mut().downcast_mut().unwrap())
+    }
+
+    fn add_slots(&mut self, count: usize) {
+        let first_id = self.entity_count;
+        self.entity_count += count;
+
+        for column in self.components.values_mut() {
+            column.storage.add_slots(count);
         }
-        None
+
+        for _ in 0..count {
+            self.tags.push(EntityTag::None);
+            self.masks.push(0);
+            self.active.push(false);
+            self.reserved.push(false);
+            self.generations.push(0);
+        }
+
+        self.free_ids.extend((first_id..self.entity_count).rev());
     }
 }
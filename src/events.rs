@@ -0,0 +1,80 @@
+use crate::entities::entity::Entity;
+
+// Things that happen during a step. Systems publish them instead of reacting
+// inline, and everything that cares (progression, stats, audio, the windows)
+// subscribes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameEvent {
+    PlayerJumped,
+    AirJumped,
+    WallJumped,
+    WallSlideStarted,
+    WallSlideEnded,
+    PlayerDashed,
+    LandedOnPlatform,
+    PickupCollected(Entity),
+    PlayerDied,
+    GoalReached,
+    LevelLoaded,
+    LevelCompleted,
+    CampaignCompleted,
+}
+
+pub trait EventSubscriber {
+    fn on_event(&mut self, event: GameEvent);
+}
+
+// Events published since the bus was last taken, kept in order
+#[derive(Default)]
+pub struct EventBus {
+    pending: Vec<GameEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn publish(&mut self, event: GameEvent) {
+        self.pending.push(event);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    // Takes the pending events, leaving the bus empty for anything published
+    // while they are handled
+    pub fn take(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+// Running totals for the campaign, kept up to date from the event stream
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub jumps: u32,
+    pub air_jumps: u32,
+    pub wall_jumps: u32,
+    pub dashes: u32,
+    pub landings: u32,
+    pub deaths: u32,
+    pub levels_completed: u32,
+}
+
+impl EventSubscriber for Stats {
+    fn on_event(&mut self, event: GameEvent) {
+        match event {
+            GameEvent::PlayerJumped => self.jumps += 1,
+            GameEvent::AirJumped => self.air_jumps += 1,
+            GameEvent::WallJumped => self.wall_jumps += 1,
+            GameEvent::PlayerDashed => self.dashes += 1,
+            GameEvent::LandedOnPlatform => self.landings += 1,
+            GameEvent::PlayerDied => self.deaths += 1,
+            GameEvent::LevelCompleted => self.levels_completed += 1,
+            _ => {}
+        }
+    }
+}
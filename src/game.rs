@@ -0,0 +1,332 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use sfml::{
+    graphics::{Color, RenderTarget, RenderWindow},
+    system::Vector2,
+    window::{Event, Key, Style},
+};
+
+use crate::{
+    audio::SoundEffects,
+    components::{ButtonState, CInput, CRender, CTransform},
+    entities::entity::Entity,
+    events::{EventSubscriber, GameEvent},
+    movement::MovementWatcher,
+    pixel_text,
+    viewport::{Viewport, VIRTUAL_HEIGHT, VIRTUAL_WIDTH},
+    window_pool::WindowPool,
+    world::{GameState, World, TIMESTEP},
+};
+
+const FRAMERATE: u32 = 60;
+// Longest frame the simulation catches up on, so a stall (e.g. dragging a
+// window) doesn't leave it running hundreds of ticks to recover
+const MAX_FRAME_TIME: f32 = 0.25;
+// How often watched movement profiles are checked for edits
+const MOVEMENT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// Presents a World through SFML: one OS window per entity with a CRender,
+// keyboard input and sound
+pub struct Game {
+    world: World,
+    viewport: Viewport,
+    player_window: RenderWindow,
+    // Windows for every other rendered entity, rebuilt when a level loads
+    window_map: HashMap<Entity, RenderWindow>,
+    window_pool: WindowPool,
+    win_window: Option<RenderWindow>,
+    // Drawn in the win window, a line per stat
+    win_summary: String,
+    sound_effects: SoundEffects,
+    movement_watcher: Option<MovementWatcher>,
+    last_movement_poll: Instant,
+}
+
+impl Game {
+    pub fn new(mut world: World) -> Self {
+        let viewport = Viewport::desktop();
+        world.set_fall_limit(viewport.bottom());
+
+        let player_window = {
+            let player = world.player();
+            let player_transform = world.player_transform();
+            let title = player
+                .get_component::<CRender>(world.entity_memory_pool())
+                .map_or("Player", |player_render| &player_render.title);
+
+            let mut player_window = RenderWindow::new(
+                viewport.video_mode(player_transform.size),
+                title,
+                Style::NONE,
+                &Default::default(),
+            );
+            // The player window alone paces rendering, however many windows are open
+            player_window.set_framerate_limit(FRAMERATE);
+
+            player_window
+        };
+
+        let mut game = Self {
+            world,
+            viewport,
+            player_window,
+            window_map: HashMap::new(),
+            window_pool: WindowPool::new(),
+            win_window: None,
+            win_summary: String::new(),
+            sound_effects: SoundEffects::new(),
+            movement_watcher: None,
+            last_movement_poll: Instant::now(),
+        };
+
+        // The world loads its first level on creation
+        game.s_events();
+
+        game
+    }
+
+    // Applies edits to the movement profiles in `directory` while the game runs
+    pub fn watch_movement(&mut self, directory: &Path) {
+        self.movement_watcher = Some(MovementWatcher::new(directory));
+    }
+
+    pub fn run(&mut self) {
+        let mut previous_frame = Instant::now();
+        let mut accumulator = 0.0;
+
+        loop {
+            self.s_input();
+            self.s_tuning();
+
+            // Step the simulation in fixed ticks for the time the last frame took
+            let now = Instant::now();
+            accumulator += (now - previous_frame).as_secs_f32().min(MAX_FRAME_TIME);
+            previous_frame = now;
+
+            while accumulator >= TIMESTEP {
+                self.world.step();
+                accumulator -= TIMESTEP;
+            }
+
+            self.s_events();
+            self.s_render();
+        }
+    }
+
+    pub fn s_input(&mut self) {
+        if self.world.game_state() == GameState::CampaignComplete {
+            self.s_campaign_complete_input();
+            return;
+        }
+
+        // Poll events
+        while let Some(event) = self.player_window.poll_event() {
+            match event {
+                Event::Closed => self.player_window.close(),
+                Event::KeyPressed { code, .. } => {
+                    if code == Key::Escape {
+                        std::process::exit(0);
+                    }
+                    if let Some(button) = button_for_key(self.world.player_input_mut(), code) {
+                        button.press();
+                    }
+                }
+                Event::KeyReleased { code, .. } => {
+                    if let Some(button) = button_for_key(self.world.player_input_mut(), code) {
+                        button.release();
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn s_tuning(&mut self) {
+        let Some(movement_watcher) = self.movement_watcher.as_mut() else {
+            return;
+        };
+        if self.last_movement_poll.elapsed() < MOVEMENT_POLL_INTERVAL {
+            return;
+        }
+        self.last_movement_poll = Instant::now();
+
+        for (name, movement) in movement_watcher.poll() {
+            match movement {
+                Ok(movement) => {
+                    self.world.set_movement_profile(&name, movement);
+                    println!("Reloaded movement profile `{}`", name);
+                }
+                // Keep the last good values until the file is fixed
+                Err(error) => eprintln!("{}", error),
+            }
+        }
+    }
+
+    pub fn s_campaign_complete_input(&mut self) {
+        let mut restart = false;
+
+        if let Some(win_window) = self.win_window.as_mut() {
+            while let Some(event) = win_window.poll_event() {
+                match event {
+                    Event::Closed => std::process::exit(0),
+                    Event::KeyPressed { code, .. } => match code {
+                        Key::Escape | Key::Q => std::process::exit(0),
+                        Key::R | Key::Enter | Key::Space => restart = true,
+                        _ => {}
+                    },
+                    _ => {}
+                }
+            }
+        }
+
+        if restart {
+            self.world.restart_campaign();
+        }
+    }
+
+    pub fn s_events(&mut self) {
+        for event in self.world.drain_events() {
+            self.sound_effects.on_event(event);
+
+            match event {
+                GameEvent::LevelLoaded => self.show_level(),
+                GameEvent::PickupCollected(pickup) => self.hide_collected(pickup),
+                GameEvent::CampaignCompleted => self.show_campaign_complete(),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn s_render(&mut self) {
+        if let Some(win_window) = self.win_window.as_mut() {
+            win_window.clear(Color::GREEN);
+            pixel_text::draw_centered(win_window, &self.win_summary, Color::BLACK);
+            win_window.display();
+            return;
+        }
+
+        let entity_memory_pool = self.world.entity_memory_pool();
+
+        for (entity, window) in self.window_map.iter_mut() {
+            window.clear(render_color(entity.get_component(entity_memory_pool)));
+
+            window.display();
+        }
+
+        // Update window position
+        let player = self.world.player();
+        let player_transform = self.world.player_transform();
+        self.viewport.place_window(
+            &mut self.player_window,
+            player_transform.position,
+            player_transform.half_size,
+        );
+
+        let mut player_color = render_color(player.get_component(entity_memory_pool));
+        // Dimmed while sliding down a wall
+        if self.world.is_wall_sliding() {
+            player_color = Color::rgb(player_color.r / 2, player_color.g / 2, player_color.b / 2);
+        }
+        self.player_window.clear(player_color);
+        self.player_window.display();
+
+        self.player_window.request_focus();
+    }
+
+    fn show_level(&mut self) {
+        if let Some(mut win_window) = self.win_window.take() {
+            win_window.close();
+        }
+
+        self.player_window.set_visible(true);
+
+        // Hand the previous level's windows back to the pool for reuse
+        for (_, window) in self.window_map.drain() {
+            self.window_pool.release(window);
+        }
+
+        let player = self.world.player();
+        let entity_memory_pool = self.world.entity_memory_pool();
+
+        for (entity, render, transform) in entity_memory_pool.query2::<CRender, CTransform>() {
+            if entity == player {
+                continue;
+            }
+
+            let mut window = self
+                .window_pool
+                .acquire(self.viewport.video_mode(transform.size), &render.title);
+            self.viewport
+                .place_window(&mut window, transform.position, transform.half_size);
+
+            self.window_map.insert(entity, window);
+        }
+
+        // Close the windows this level has no entities for
+        self.window_pool.close_idle();
+    }
+
+    fn hide_collected(&mut self, pickup: Entity) {
+        if let Some(mut window) = self.window_map.remove(&pickup) {
+            window.set_visible(false);
+            self.window_pool.release(window);
+        }
+    }
+
+    fn show_campaign_complete(&mut self) {
+        let elapsed = self.world.campaign_time();
+        self.win_summary = format!(
+            "Deaths {}\nTime {}:{:02}.{:03}",
+            self.world.deaths(),
+            elapsed.as_secs() / 60,
+            elapsed.as_secs() % 60,
+            elapsed.subsec_millis(),
+        );
+
+        // Hide the level
+        self.player_window.set_visible(false);
+        for window in self.window_map.values_mut() {
+            window.set_visible(false);
+        }
+
+        let win_window_size = Vector2::new(600.0, 150.0);
+        let mut win_window = RenderWindow::new(
+            self.viewport.video_mode(win_window_size),
+            "Campaign complete! R: restart, Esc: quit",
+            Style::TITLEBAR | Style::CLOSE,
+            &Default::default(),
+        );
+        win_window.set_framerate_limit(FRAMERATE);
+        self.viewport.place_window(
+            &mut win_window,
+            Vector2::new(VIRTUAL_WIDTH / 2.0, VIRTUAL_HEIGHT / 2.0),
+            win_window_size / 2.0,
+        );
+        win_window.request_focus();
+
+        self.win_window = Some(win_window);
+    }
+}
+
+fn button_for_key(input: &mut CInput, code: Key) -> Option<&mut ButtonState> {
+    match code {
+        Key::Up | Key::W => Some(&mut input.up),
+        Key::Down | Key::S => Some(&mut input.down),
+        Key::Left | Key::A => Some(&mut input.left),
+        Key::Right | Key::D => Some(&mut input.right),
+        Key::Space => Some(&mut input.space),
+        Key::LShift | Key::X => Some(&mut input.dash),
+        _ => None,
+    }
+}
+
+fn render_color(render: Option<&CRender>) -> Color {
+    match render {
+        Some(render) => Color::rgb(render.color[0], render.color[1], render.color[2]),
+        None => Color::BLACK,
+    }
+}
@@ -1,85 +1,179 @@
+use std::path::Path;
+
+use serde::Deserialize;
 use sfml::system::Vector2;
 
-use crate::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::{
+    assets::{self, AssetError, AssetKind},
+    movement::MovementProfiles,
+    prefabs::Prefabs,
+};
+
+// Relative to the assets directory
+pub const LEVELS_DIRECTORY: &str = "levels";
 
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct PlatformRect {
     pub position: Vector2<f32>,
     pub size: Vector2<f32>,
 }
 
+// Any other entity placed in the level, spawned from the named prefab
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LevelEntity {
+    pub prefab: String,
+    pub position: Vector2<f32>,
+    // Overrides the prefab's size
+    #[serde(default)]
+    pub size: Option<Vector2<f32>>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Level {
     pub platforms: Vec<PlatformRect>,
+    #[serde(default)]
+    pub entities: Vec<LevelEntity>,
+    // Name of the movement profile the player uses, the default one if unset
+    #[serde(default)]
+    pub movement: Option<String>,
+    // Whether the player can dash in this level
+    #[serde(default)]
+    pub dash: bool,
+    // Extra jumps the player can make before landing, on top of any pickups
+    #[serde(default)]
+    pub air_jumps: u32,
     pub player_start: Vector2<f32>,
     pub goal: Vector2<f32>,
 }
 
-pub fn get_levels() -> Vec<Level> {
-    let level_1 = Level {
-        platforms: vec![
-            PlatformRect {
-                position: Vector2::new(*SCREEN_WIDTH as f32 / 2.0, *SCREEN_HEIGHT as f32 * 0.75),
-                size: Vector2::new(1000.0, 50.0),
-            },
-            PlatformRect {
-                position: Vector2::new(
-                    *SCREEN_WIDTH as f32 / 2.0,
-                    *SCREEN_HEIGHT as f32 * 0.5 + 200.0,
-                ),
-                size: Vector2::new(250.0, 100.0),
-            },
-            // PlatformRect {
-            //     position: Vector2::new(*SCREEN_WIDTH as f32 * 0.25, *SCREEN_HEIGHT as f32 * 0.5),
-            //     size: Vector2::new(50.0, 500.0),
-            // },
-            // PlatformRect {
-            //     position: Vector2::new(*SCREEN_WIDTH as f32 * 0.75, *SCREEN_HEIGHT as f32 * 0.5),
-            //     size: Vector2::new(50.0, 500.0),
-            // },
-        ],
-        player_start: Vector2::new(
-            *SCREEN_WIDTH as f32 / 4.0 + 100.0,
-            *SCREEN_HEIGHT as f32 * 0.75 - 75.0,
-        ),
-        goal: Vector2::new(
-            *SCREEN_WIDTH as f32 * 3.0 / 4.0 - 100.0,
-            *SCREEN_HEIGHT as f32 * 0.75 - 75.0,
-        ),
-    };
-
-    let level_2 = Level {
-        platforms: vec![
-            PlatformRect {
-                position: Vector2::new(*SCREEN_WIDTH as f32 / 2.0, *SCREEN_HEIGHT as f32 * 0.75),
-                size: Vector2::new(1000.0, 50.0),
-            },
-            PlatformRect {
-                position: Vector2::new(*SCREEN_WIDTH as f32 * 0.65, *SCREEN_HEIGHT as f32 * 0.25),
-                size: Vector2::new(400.0, 50.0),
-            },
-            PlatformRect {
-                position: Vector2::new(*SCREEN_WIDTH as f32 * 0.45, *SCREEN_HEIGHT as f32 * 0.2),
-                size: Vector2::new(50.0, 800.0),
-            },
-            PlatformRect {
-                position: Vector2::new(*SCREEN_WIDTH as f32 * 0.55, *SCREEN_HEIGHT as f32 * 0.5),
-                size: Vector2::new(50.0, 500.0),
-            },
-            // PlatformRect {
-            //     position: Vector2::new(*SCREEN_WIDTH as f32 * 0.75, *SCREEN_HEIGHT as f32 * 0.5),
-            //     size: Vector2::new(50.0, 500.0),
-            // },
-        ],
-        player_start: Vector2::new(
-            *SCREEN_WIDTH as f32 / 4.0 + 100.0,
-            *SCREEN_HEIGHT as f32 * 0.75 - 75.0,
-        ),
-        goal: Vector2::new(
-            *SCREEN_WIDTH as f32 * 3.0 / 4.0 - 100.0,
-            *SCREEN_HEIGHT as f32 * 0.25 - 75.0,
-        ),
-    };
-
-    let levels = vec![level_1, level_2];
-
-    return levels;
+impl Level {
+    pub fn from_file(path: &Path) -> Result<Self, AssetError> {
+        let contents = assets::read_file(AssetKind::Level, path)?;
+        let level: Level = assets::parse_json(AssetKind::Level, &contents, path)?;
+
+        level.validate(path)?;
+
+        Ok(level)
+    }
+
+    fn validate(&self, path: &Path) -> Result<(), AssetError> {
+        let invalid = |field: String, message: &str| AssetError::InvalidField {
+            kind: AssetKind::Level,
+            path: path.to_path_buf(),
+            field,
+            message: message.to_string(),
+        };
+
+        if !is_finite(self.player_start) {
+            return Err(invalid("player_start".to_string(), "must be finite"));
+        }
+        if !is_finite(self.goal) {
+            return Err(invalid("goal".to_string(), "must be finite"));
+        }
+
+        for (i, platform) in self.platforms.iter().enumerate() {
+            if !is_finite(platform.position) {
+                return Err(invalid(
+                    format!("platforms[{}].position", i),
+                    "must be finite",
+                ));
+            }
+            if !is_positive_size(platform.size) {
+                return Err(invalid(
+                    format!("platforms[{}].size", i),
+                    "must have a finite, positive width and height",
+                ));
+            }
+        }
+
+        for (i, entity) in self.entities.iter().enumerate() {
+            if !is_finite(entity.position) {
+                return Err(invalid(
+                    format!("entities[{}].position", i),
+                    "must be finite",
+                ));
+            }
+            if entity.size.is_some_and(|size| !is_positive_size(size)) {
+                return Err(invalid(
+                    format!("entities[{}].size", i),
+                    "must have a finite, positive width and height",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Every prefab and movement profile the level names has to be loaded
+    fn check_assets(
+        &self,
+        path: &Path,
+        prefabs: &Prefabs,
+        movement_profiles: &MovementProfiles,
+    ) -> Result<(), AssetError> {
+        let invalid = |field: String, message: String| AssetError::InvalidField {
+            kind: AssetKind::Level,
+            path: path.to_path_buf(),
+            field,
+            message,
+        };
+
+        if let Some(name) = &self.movement {
+            if !movement_profiles.contains(name) {
+                return Err(invalid(
+                    "movement".to_string(),
+                    format!("names unknown movement profile `{}`", name),
+                ));
+            }
+        }
+
+        for (i, entity) in self.entities.iter().enumerate() {
+            if !prefabs.contains(&entity.prefab) {
+                return Err(invalid(
+                    format!("entities[{}].prefab", i),
+                    format!("names unknown prefab `{}`", entity.prefab),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_finite(vector: Vector2<f32>) -> bool {
+    vector.x.is_finite() && vector.y.is_finite()
+}
+
+fn is_positive_size(size: Vector2<f32>) -> bool {
+    is_finite(size) && size.x > 0.0 && size.y > 0.0
+}
+
+// Loads every `.json` file in `directory`, ordered by file name, checking
+// that each only uses the given prefabs and movement profiles
+pub fn load_levels(
+    directory: &Path,
+    prefabs: &Prefabs,
+    movement_profiles: &MovementProfiles,
+) -> Result<Vec<Level>, AssetError> {
+    let paths = assets::json_files(AssetKind::Level, directory)?;
+
+    if paths.is_empty() {
+        return Err(AssetError::Missing {
+            kind: AssetKind::Level,
+            path: directory.to_path_buf(),
+            what: "level files".to_string(),
+        });
+    }
+
+    paths
+        .iter()
+        .map(|path| {
+            let level = Level::from_file(path)?;
+            level.check_assets(path, prefabs, movement_profiles)?;
+            Ok(level)
+        })
+        .collect()
 }
@@ -0,0 +1,14 @@
+pub mod assets;
+pub mod audio;
+pub mod collision;
+pub mod components;
+pub mod entities;
+pub mod events;
+pub mod game;
+pub mod levels;
+pub mod movement;
+pub mod pixel_text;
+pub mod prefabs;
+pub mod viewport;
+pub mod window_pool;
+pub mod world;
@@ -0,0 +1,225 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::Deserialize;
+use sfml::system::Vector2;
+
+use crate::assets::{self, AssetError, AssetKind, ASSETS_DIRECTORY};
+
+// Relative to the assets directory
+pub const MOVEMENT_DIRECTORY: &str = "movement";
+
+// Levels that don't pick a profile use this one, so every set has to define it
+pub const DEFAULT_MOVEMENT: &str = "default";
+
+// How the player controller feels: the speeds, accelerations and grace
+// periods behind running and jumping
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MovementProfile {
+    pub gravity: f32,
+    pub jump_speed: f32,
+    pub wall_jump_speed: f32,
+    // Releasing jump while still rising divides the upward speed by this
+    pub jump_cut_divisor: f32,
+    pub max_speed: f32,
+    // x while running, y while coming to a stop
+    pub acceleration_scale: Vector2<f32>,
+    // Air control after a wall jump, in px/s^2
+    pub wall_jump_air_acceleration: f32,
+    // Fastest the player falls while pushing into a wall
+    pub wall_slide_speed: f32,
+    pub dash_speed: f32,
+    pub dash_duration: f32,
+    // How long after a dash ends before the next one can start
+    pub dash_cooldown: f32,
+    // Dashes allowed before touching the ground again
+    pub air_dashes: u32,
+    // How long a jump press is remembered before landing
+    pub jump_input_duration: f32,
+    // How long after leaving the ground a jump still counts as a normal jump
+    pub grounded_duration: f32,
+    // How long after leaving a wall a wall jump is still allowed
+    pub wall_contact_duration: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct MovementProfiles {
+    profiles: HashMap<String, MovementProfile>,
+}
+
+impl MovementProfile {
+    pub fn from_file(path: &Path) -> Result<Self, AssetError> {
+        let contents = assets::read_file(AssetKind::Movement, path)?;
+
+        Self::parse(&contents, path)
+    }
+
+    fn parse(contents: &str, path: &Path) -> Result<Self, AssetError> {
+        let profile: MovementProfile = assets::parse_json(AssetKind::Movement, contents, path)?;
+
+        profile.validate(path)?;
+
+        Ok(profile)
+    }
+
+    fn validate(&self, path: &Path) -> Result<(), AssetError> {
+        let invalid = |field: &str, message: &str| AssetError::InvalidField {
+            kind: AssetKind::Movement,
+            path: path.to_path_buf(),
+            field: field.to_string(),
+            message: message.to_string(),
+        };
+
+        let positive = [
+            ("jump_speed", self.jump_speed),
+            ("wall_jump_speed", self.wall_jump_speed),
+            ("dash_speed", self.dash_speed),
+            ("dash_duration", self.dash_duration),
+            ("acceleration_scale.x", self.acceleration_scale.x),
+            ("acceleration_scale.y", self.acceleration_scale.y),
+        ];
+        for (field, value) in positive {
+            if !(value > 0.0 && value.is_finite()) {
+                return Err(invalid(field, "must be finite and positive"));
+            }
+        }
+
+        let not_negative = [
+            ("gravity", self.gravity),
+            ("max_speed", self.max_speed),
+            (
+                "wall_jump_air_acceleration",
+                self.wall_jump_air_acceleration,
+            ),
+            ("wall_slide_speed", self.wall_slide_speed),
+            ("dash_cooldown", self.dash_cooldown),
+            ("jump_input_duration", self.jump_input_duration),
+            ("grounded_duration", self.grounded_duration),
+            ("wall_contact_duration", self.wall_contact_duration),
+        ];
+        for (field, value) in not_negative {
+            if !(value >= 0.0 && value.is_finite()) {
+                return Err(invalid(field, "must be finite and not negative"));
+            }
+        }
+
+        if !(self.jump_cut_divisor >= 1.0 && self.jump_cut_divisor.is_finite()) {
+            return Err(invalid("jump_cut_divisor", "must be finite and at least 1"));
+        }
+
+        Ok(())
+    }
+}
+
+impl MovementProfiles {
+    // The profiles shipped in assets/movement, compiled in
+    pub fn builtin() -> Self {
+        let builtin = [
+            ("default", include_str!("../assets/movement/default.json")),
+            ("floaty", include_str!("../assets/movement/floaty.json")),
+            ("tight", include_str!("../assets/movement/tight.json")),
+        ];
+
+        let profiles = builtin
+            .into_iter()
+            .map(|(name, contents)| {
+                let path = Path::new(ASSETS_DIRECTORY)
+                    .join(MOVEMENT_DIRECTORY)
+                    .join(format!("{}.json", name));
+                MovementProfile::parse(contents, &path)
+                    .map(|profile| (name.to_string(), profile))
+                    .unwrap_or_else(|error| panic!("{}", error))
+            })
+            .collect();
+
+        Self { profiles }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MovementProfile> {
+        self.profiles.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.profiles.contains_key(name)
+    }
+
+    pub fn insert(&mut self, name: &str, profile: MovementProfile) {
+        self.profiles.insert(name.to_string(), profile);
+    }
+}
+
+// Loads every `.json` file in `directory` as a profile named after the file
+pub fn load_movement_profiles(directory: &Path) -> Result<MovementProfiles, AssetError> {
+    let mut profiles = HashMap::new();
+    for path in assets::json_files(AssetKind::Movement, directory)? {
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        profiles.insert(name, MovementProfile::from_file(&path)?);
+    }
+
+    if !profiles.contains_key(DEFAULT_MOVEMENT) {
+        return Err(AssetError::Missing {
+            kind: AssetKind::Movement,
+            path: directory.to_path_buf(),
+            what: format!("{}.json movement profile", DEFAULT_MOVEMENT),
+        });
+    }
+
+    Ok(MovementProfiles { profiles })
+}
+
+// Watches a movement directory for edited profiles, so they can be applied to
+// a running game
+pub struct MovementWatcher {
+    directory: PathBuf,
+    modified: HashMap<PathBuf, SystemTime>,
+}
+
+impl MovementWatcher {
+    // Files already there count as unchanged
+    pub fn new(directory: &Path) -> Self {
+        let mut watcher = Self {
+            directory: directory.to_path_buf(),
+            modified: HashMap::new(),
+        };
+        watcher.poll();
+
+        watcher
+    }
+
+    // Reloads every profile whose file was added or modified since the last
+    // poll. A file that fails to load is reported and tried again once it
+    // changes.
+    pub fn poll(&mut self) -> Vec<(String, Result<MovementProfile, AssetError>)> {
+        let mut changed = Vec::new();
+
+        // The directory may be mid-edit, so unreadable entries are skipped
+        let Ok(entries) = fs::read_dir(&self.directory) else {
+            return changed;
+        };
+
+        for path in entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(modified) = fs::metadata(&path).and_then(|metadata| metadata.modified()) else {
+                continue;
+            };
+
+            if self.modified.insert(path.clone(), modified) != Some(modified) {
+                let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+                changed.push((name, MovementProfile::from_file(&path)));
+            }
+        }
+
+        changed.sort_by(|(a, _), (b, _)| a.cmp(b));
+        changed
+    }
+}
@@ -0,0 +1,96 @@
+use sfml::{
+    graphics::{Color, RectangleShape, RenderTarget, Shape, Transformable},
+    system::Vector2,
+};
+
+// Glyphs are drawn as blocks on a grid this many pixels across and down
+pub const GLYPH_WIDTH: u32 = 3;
+pub const GLYPH_HEIGHT: u32 = 5;
+// Blank pixels between neighbouring glyphs and lines
+const SPACING: u32 = 1;
+
+// The rows of a glyph from the top, with bit 2 as the leftmost pixel. Only
+// the characters the game shows are covered, the rest draw as blanks.
+fn glyph(character: char) -> [u8; GLYPH_HEIGHT as usize] {
+    match character.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        _ => [0; GLYPH_HEIGHT as usize],
+    }
+}
+
+// Size of `text` in font pixels, with a line per `\n`
+pub fn text_size(text: &str) -> Vector2<u32> {
+    let lines = text.lines().count() as u32;
+    let longest = text
+        .lines()
+        .map(|line| line.chars().count() as u32)
+        .max()
+        .unwrap_or(0);
+
+    Vector2::new(
+        (longest * (GLYPH_WIDTH + SPACING)).saturating_sub(SPACING),
+        (lines * (GLYPH_HEIGHT + SPACING)).saturating_sub(SPACING),
+    )
+}
+
+// Draws `text` as large as it fits in `target` with a glyph's width of margin
+// around it, centered
+pub fn draw_centered(target: &mut impl RenderTarget, text: &str, color: Color) {
+    let size = text_size(text);
+    if size.x == 0 || size.y == 0 {
+        return;
+    }
+
+    let margin = 2 * GLYPH_WIDTH;
+    let target_size = target.size();
+    let scale = (target_size.x as f32 / (size.x + margin) as f32)
+        .min(target_size.y as f32 / (size.y + margin) as f32)
+        .floor()
+        .max(1.0);
+    let origin = Vector2::new(
+        (target_size.x as f32 - size.x as f32 * scale) / 2.0,
+        (target_size.y as f32 - size.y as f32 * scale) / 2.0,
+    );
+
+    let mut block = RectangleShape::with_size(Vector2::new(scale, scale));
+    block.set_fill_color(color);
+
+    for (row, line) in text.lines().enumerate() {
+        let top = row as u32 * (GLYPH_HEIGHT + SPACING);
+
+        for (column, character) in line.chars().enumerate() {
+            let left = column as u32 * (GLYPH_WIDTH + SPACING);
+
+            for (y, bits) in glyph(character).into_iter().enumerate() {
+                for x in 0..GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - x)) != 0 {
+                        block.set_position(
+                            origin
+                                + Vector2::new((left + x) as f32, (top + y as u32) as f32) * scale,
+                        );
+                        target.draw(&block);
+                    }
+                }
+            }
+        }
+    }
+}
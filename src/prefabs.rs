@@ -0,0 +1,187 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::Deserialize;
+use sfml::system::Vector2;
+
+use crate::{
+    assets::{self, AssetError, AssetKind, ASSETS_DIRECTORY},
+    components::{CInput, CPickup, CRender, CTransform},
+    entities::{
+        entity::Entity, entity_manager::EntityManager, entity_memory_pool::EntityMemoryPool,
+        EntityTag,
+    },
+};
+
+// Relative to the assets directory
+pub const PREFABS_FILE: &str = "prefabs.json";
+
+// The world spawns these itself, so every prefab set has to define them with
+// the tag the world looks them up by
+const REQUIRED_PREFABS: [(&str, EntityTag); 3] = [
+    ("player", EntityTag::Player),
+    ("goal", EntityTag::Goal),
+    ("platform", EntityTag::Platform),
+];
+
+// A named template for an entity: its tag and the components it starts with
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Prefab {
+    pub tag: EntityTag,
+    pub size: Vector2<f32>,
+    #[serde(default)]
+    pub max_speed: Option<f32>,
+    // Whether the entity reads player input
+    #[serde(default)]
+    pub input: bool,
+    // Entities without one have no window
+    #[serde(default)]
+    pub render: Option<CRender>,
+    #[serde(default)]
+    pub pickup: Option<CPickup>,
+}
+
+impl Prefab {
+    // Spawns the entity at `position`, optionally overriding the prefab's size
+    pub fn spawn(
+        &self,
+        position: Vector2<f32>,
+        size: Option<Vector2<f32>>,
+        entity_manager: &mut EntityManager,
+        entity_memory_pool: &mut EntityMemoryPool,
+    ) -> Entity {
+        // The slot is only reserved until the manager's next update, so the
+        // components can go straight into the pool
+        let entity = entity_manager.add_entity(self.tag, entity_memory_pool);
+
+        let mut transform = CTransform::new()
+            .with_position(position)
+            .with_size(size.unwrap_or(self.size));
+        if let Some(max_speed) = self.max_speed {
+            transform = transform.with_max_speed(max_speed);
+        }
+        entity_memory_pool.add_component(entity, transform);
+
+        if self.input {
+            entity_memory_pool.add_component(entity, CInput::new());
+        }
+        if let Some(render) = &self.render {
+            entity_memory_pool.add_component(entity, render.clone());
+        }
+        if let Some(pickup) = &self.pickup {
+            entity_memory_pool.add_component(entity, pickup.clone());
+        }
+
+        entity
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(transparent)]
+pub struct Prefabs {
+    prefabs: HashMap<String, Prefab>,
+}
+
+impl Prefabs {
+    pub fn from_file(path: &Path) -> Result<Self, AssetError> {
+        let contents = assets::read_file(AssetKind::Prefab, path)?;
+
+        Self::parse(&contents, path)
+    }
+
+    // The prefabs shipped in assets/prefabs.json, compiled in
+    pub fn builtin() -> Self {
+        Self::parse(
+            include_str!("../assets/prefabs.json"),
+            &Path::new(ASSETS_DIRECTORY).join(PREFABS_FILE),
+        )
+        .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Prefab> {
+        self.prefabs.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.prefabs.contains_key(name)
+    }
+
+    // Spawns the named prefab, panicking if there is no such prefab
+    pub fn spawn(
+        &self,
+        name: &str,
+        position: Vector2<f32>,
+        size: Option<Vector2<f32>>,
+        entity_manager: &mut EntityManager,
+        entity_memory_pool: &mut EntityMemoryPool,
+    ) -> Entity {
+        let prefab = self
+            .get(name)
+            .unwrap_or_else(|| panic!("no prefab named `{}`", name));
+
+        prefab.spawn(position, size, entity_manager, entity_memory_pool)
+    }
+
+    fn parse(contents: &str, path: &Path) -> Result<Self, AssetError> {
+        let prefabs: Prefabs = assets::parse_json(AssetKind::Prefab, contents, path)?;
+
+        prefabs.validate(path)?;
+
+        Ok(prefabs)
+    }
+
+    fn validate(&self, path: &Path) -> Result<(), AssetError> {
+        let invalid = |field: String, message: &str| AssetError::InvalidField {
+            kind: AssetKind::Prefab,
+            path: path.to_path_buf(),
+            field,
+            message: message.to_string(),
+        };
+
+        for (name, tag) in REQUIRED_PREFABS {
+            let Some(prefab) = self.get(name) else {
+                return Err(invalid(name.to_string(), "must be defined"));
+            };
+            if prefab.tag != tag {
+                return Err(invalid(
+                    format!("{}.tag", name),
+                    &format!("must be `{:?}`", tag),
+                ));
+            }
+        }
+        // Otherwise nothing in the world reads the player's input
+        if !self.prefabs["player"].input {
+            return Err(invalid("player.input".to_string(), "must be true"));
+        }
+
+        // Sorted so the same file always reports the same error
+        let mut names: Vec<&String> = self.prefabs.keys().collect();
+        names.sort();
+
+        for name in names {
+            let prefab = &self.prefabs[name];
+
+            if !(prefab.size.x > 0.0
+                && prefab.size.y > 0.0
+                && prefab.size.x.is_finite()
+                && prefab.size.y.is_finite())
+            {
+                return Err(invalid(
+                    format!("{}.size", name),
+                    "must have a finite, positive width and height",
+                ));
+            }
+            if prefab
+                .max_speed
+                .is_some_and(|max_speed| !(max_speed >= 0.0 && max_speed.is_finite()))
+            {
+                return Err(invalid(
+                    format!("{}.max_speed", name),
+                    "must be finite and not negative",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use sfml::{graphics::RenderWindow, system::Vector2, window::VideoMode};
+
+// Levels are authored in this virtual resolution and scaled to fit the desktop
+pub const VIRTUAL_WIDTH: f32 = 1920.0;
+pub const VIRTUAL_HEIGHT: f32 = 1080.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    screen_size: Vector2<f32>,
+    scale: f32,
+    offset: Vector2<f32>,
+}
+
+impl Viewport {
+    pub fn new(screen_size: Vector2<f32>) -> Self {
+        // Uniform scale so nothing is stretched, centered on the screen
+        let scale = (screen_size.x / VIRTUAL_WIDTH).min(screen_size.y / VIRTUAL_HEIGHT);
+        let offset = (screen_size - Vector2::new(VIRTUAL_WIDTH, VIRTUAL_HEIGHT) * scale) / 2.0;
+
+        Self {
+            screen_size,
+            scale,
+            offset,
+        }
+    }
+
+    pub fn desktop() -> Self {
+        let desktop_mode = VideoMode::desktop_mode();
+
+        Self::new(Vector2::new(
+            desktop_mode.width as f32,
+            desktop_mode.height as f32,
+        ))
+    }
+
+    pub fn screen_position(&self, position: Vector2<f32>) -> Vector2<f32> {
+        self.offset + position * self.scale
+    }
+
+    pub fn window_size(&self, size: Vector2<f32>) -> Vector2<u32> {
+        Vector2::new(
+            ((size.x * self.scale).round() as u32).max(1),
+            ((size.y * self.scale).round() as u32).max(1),
+        )
+    }
+
+    // The virtual y coordinate of the bottom edge of the screen, which may lie
+    // below VIRTUAL_HEIGHT when the desktop is taller than 16:9
+    pub fn bottom(&self) -> f32 {
+        (self.screen_size.y - self.offset.y) / self.scale
+    }
+
+    pub fn video_mode(&self, size: Vector2<f32>) -> VideoMode {
+        let screen_size = self.window_size(size);
+
+        VideoMode::new(screen_size.x, screen_size.y, 32)
+    }
+
+    // Moves a window so it covers the virtual rect centered on `position`
+    pub fn place_window(
+        &self,
+        window: &mut RenderWindow,
+        position: Vector2<f32>,
+        half_size: Vector2<f32>,
+    ) {
+        let top_left = self.screen_position(position - half_size);
+
+        window.set_position(Vector2::new(
+            top_left.x.round() as i32,
+            top_left.y.round() as i32,
+        ));
+    }
+}
@@ -0,0 +1,53 @@
+use sfml::{
+    graphics::{RenderTarget, RenderWindow},
+    system::Vector2,
+    window::{Style, VideoMode},
+};
+
+// Keeps released OS windows alive so they can be resized and reused instead of
+// closing them and opening new ones
+#[derive(Default)]
+pub struct WindowPool {
+    idle: Vec<RenderWindow>,
+}
+
+impl WindowPool {
+    pub fn new() -> Self {
+        Self { idle: Vec::new() }
+    }
+
+    pub fn acquire(&mut self, mode: VideoMode, title: &str) -> RenderWindow {
+        let size = Vector2::new(mode.width, mode.height);
+
+        // Prefer a window that is already the right size
+        let index = self
+            .idle
+            .iter()
+            .position(|window| window.size() == size)
+            .or_else(|| self.idle.len().checked_sub(1));
+
+        match index {
+            Some(index) => {
+                let mut window = self.idle.swap_remove(index);
+                if window.size() != size {
+                    window.set_size(size);
+                }
+                window.set_title(title);
+                window.set_visible(true);
+                window
+            }
+            None => RenderWindow::new(mode, title, Style::NONE, &Default::default()),
+        }
+    }
+
+    pub fn release(&mut self, window: RenderWindow) {
+        self.idle.push(window);
+    }
+
+    // Closes every released window that was not acquired again
+    pub fn close_idle(&mut self) {
+        for mut window in self.idle.drain(..) {
+            window.close();
+        }
+    }
+}
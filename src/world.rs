@@ -0,0 +1,895 @@
+use std::{collections::VecDeque, time::Duration};
+
+use sfml::system::Vector2;
+
+use crate::{
+    collision::{self, Contacts},
+    components::{CInput, CPickup, CTransform, Component},
+    entities::{
+        entity::Entity, entity_manager::EntityManager, entity_memory_pool::EntityMemoryPool,
+        EntityTag,
+    },
+    events::{EventBus, EventSubscriber, GameEvent, Stats},
+    levels::Level,
+    movement::{MovementProfile, MovementProfiles, DEFAULT_MOVEMENT},
+    prefabs::Prefabs,
+    viewport::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH},
+};
+
+// The simulation always advances in ticks of this many seconds, however fast
+// the presentation renders
+pub const TIMESTEP: f32 = 1.0 / 60.0;
+
+// Handled events kept for drain_events. A world nobody drains drops the
+// oldest past this, rather than growing forever.
+pub const MAX_HANDLED_EVENTS: usize = 1024;
+
+// Less time than this left on a dash is float error, not another tick of dash
+const DASH_EPSILON: f32 = 1e-4;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameState {
+    Playing,
+    CampaignComplete,
+}
+
+// The simulation: entities, player physics, collision and level progression.
+// It owns no windows, so it can be stepped headlessly with scripted input.
+pub struct World {
+    entity_manager: EntityManager,
+    entity_memory_pool: EntityMemoryPool,
+    player: Entity,
+    jump_input_timer: f32,
+    grounded_timer: f32,
+    wall_contact_timer: f32,
+    // 1.0 when the wall last touched is to the right of the player, -1.0 when to the left
+    wall_contact_side: f32,
+    goal: Entity,
+    level_index: usize,
+    wall_jump_physics: bool,
+    wall_sliding: bool,
+    // Time left in the current dash, and until the next one is allowed
+    dash_timer: f32,
+    dash_cooldown_timer: f32,
+    dash_velocity: Vector2<f32>,
+    dash_charges: u32,
+    // 1.0 when the player last moved right, -1.0 when left
+    facing: f32,
+    // Jumps left before landing, out of the level's and its collected pickups'
+    air_jumps: u32,
+    max_air_jumps: u32,
+    levels: Vec<Level>,
+    prefabs: Prefabs,
+    movement_profiles: MovementProfiles,
+    // The profile of the current level
+    movement: MovementProfile,
+    // Everything spawned by the current level, destroyed when the next one loads
+    level_entities: Vec<Entity>,
+    player_contacts: Contacts,
+    fall_limit: f32,
+    game_state: GameState,
+    stats: Stats,
+    // Kept in f64 so summing many small steps doesn't drift
+    campaign_time: f64,
+    events: EventBus,
+    // Reactions added from outside, e.g. effects
+    subscribers: Vec<Box<dyn EventSubscriber + Send>>,
+    // Handled events, until the presentation layer drains them
+    handled_events: VecDeque<GameEvent>,
+}
+
+impl World {
+    pub fn new(levels: Vec<Level>) -> Self {
+        Self::with_prefabs(levels, Prefabs::builtin())
+    }
+
+    pub fn with_prefabs(levels: Vec<Level>, prefabs: Prefabs) -> Self {
+        Self::with_assets(levels, prefabs, MovementProfiles::builtin())
+    }
+
+    pub fn with_assets(
+        levels: Vec<Level>,
+        prefabs: Prefabs,
+        movement_profiles: MovementProfiles,
+    ) -> Self {
+        assert!(!levels.is_empty(), "a world needs at least one level");
+        let movement = *movement_profiles
+            .get(DEFAULT_MOVEMENT)
+            .unwrap_or_else(|| panic!("no `{}` movement profile", DEFAULT_MOVEMENT));
+        for (i, level) in levels.iter().enumerate() {
+            if let Some(name) = &level.movement {
+                assert!(
+                    movement_profiles.contains(name),
+                    "level {} uses unknown movement profile `{}`",
+                    i,
+                    name
+                );
+            }
+            for entity in level.entities.iter() {
+                assert!(
+                    prefabs.contains(&entity.prefab),
+                    "level {} uses unknown prefab `{}`",
+                    i,
+                    entity.prefab
+                );
+            }
+        }
+
+        let mut entity_memory_pool = EntityMemoryPool::new(20000);
+        let mut entity_manager = EntityManager::new();
+
+        let player = prefabs.spawn(
+            "player",
+            Vector2::new(VIRTUAL_WIDTH / 2.0, VIRTUAL_HEIGHT / 2.0),
+            None,
+            &mut entity_manager,
+            &mut entity_memory_pool,
+        );
+        let goal = prefabs.spawn(
+            "goal",
+            Vector2::new(VIRTUAL_WIDTH / 2.0, 50.0),
+            None,
+            &mut entity_manager,
+            &mut entity_memory_pool,
+        );
+
+        let mut world = Self {
+            entity_manager,
+            entity_memory_pool,
+            player,
+            jump_input_timer: 0.0,
+            grounded_timer: 0.0,
+            wall_contact_timer: 0.0,
+            wall_contact_side: 0.0,
+            goal,
+            level_index: 0,
+            wall_jump_physics: false,
+            wall_sliding: false,
+            dash_timer: 0.0,
+            dash_cooldown_timer: 0.0,
+            dash_velocity: Vector2::new(0.0, 0.0),
+            dash_charges: 0,
+            facing: 1.0,
+            air_jumps: 0,
+            max_air_jumps: 0,
+            levels,
+            prefabs,
+            movement,
+            movement_profiles,
+            level_entities: Vec::new(),
+            player_contacts: Contacts::default(),
+            fall_limit: VIRTUAL_HEIGHT,
+            game_state: GameState::Playing,
+            stats: Stats::default(),
+            campaign_time: 0.0,
+            events: EventBus::new(),
+            subscribers: Vec::new(),
+            handled_events: VecDeque::new(),
+        };
+
+        world.load_level(0);
+        world.s_events();
+
+        world
+    }
+
+    pub fn player(&self) -> Entity {
+        self.player
+    }
+
+    pub fn goal(&self) -> Entity {
+        self.goal
+    }
+
+    pub fn entity_manager(&self) -> &EntityManager {
+        &self.entity_manager
+    }
+
+    pub fn entity_memory_pool(&self) -> &EntityMemoryPool {
+        &self.entity_memory_pool
+    }
+
+    pub fn player_transform(&self) -> &CTransform {
+        self.player
+            .get_component::<CTransform>(&self.entity_memory_pool)
+            .unwrap()
+    }
+
+    pub fn player_transform_mut(&mut self) -> &mut CTransform {
+        self.player
+            .get_component_mut::<CTransform>(&mut self.entity_memory_pool)
+            .unwrap()
+    }
+
+    pub fn goal_transform(&self) -> &CTransform {
+        self.goal
+            .get_component::<CTransform>(&self.entity_memory_pool)
+            .unwrap()
+    }
+
+    // The profile the player currently moves with
+    pub fn movement(&self) -> &MovementProfile {
+        &self.movement
+    }
+
+    // Name of the movement profile the current level uses
+    pub fn movement_name(&self) -> &str {
+        self.levels[self.level_index]
+            .movement
+            .as_deref()
+            .unwrap_or(DEFAULT_MOVEMENT)
+    }
+
+    // Replaces a profile, applying it straight away if the current level uses
+    // it. The player keeps their position and velocity.
+    pub fn set_movement_profile(&mut self, name: &str, movement: MovementProfile) {
+        self.movement_profiles.insert(name, movement);
+
+        if self.movement_name() == name {
+            self.movement = movement;
+            self.player_transform_mut().max_speed = movement.max_speed;
+        }
+    }
+
+    // Falling down a wall while pushing into it, at no more than the
+    // profile's wall_slide_speed
+    pub fn is_wall_sliding(&self) -> bool {
+        self.wall_sliding
+    }
+
+    pub fn is_dashing(&self) -> bool {
+        self.dash_timer > 0.0
+    }
+
+    // Dashes left before the player has to touch the ground again
+    pub fn dash_charges(&self) -> u32 {
+        self.dash_charges
+    }
+
+    pub fn air_jumps(&self) -> u32 {
+        self.air_jumps
+    }
+
+    pub fn max_air_jumps(&self) -> u32 {
+        self.max_air_jumps
+    }
+
+    // Every surface the player touched during the last step
+    pub fn player_contacts(&self) -> Contacts {
+        self.player_contacts
+    }
+
+    pub fn player_input_mut(&mut self) -> &mut CInput {
+        self.player
+            .get_component_mut::<CInput>(&mut self.entity_memory_pool)
+            .unwrap()
+    }
+
+    pub fn level_index(&self) -> usize {
+        self.level_index
+    }
+
+    pub fn game_state(&self) -> GameState {
+        self.game_state
+    }
+
+    pub fn deaths(&self) -> u32 {
+        self.stats.deaths
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    // Simulated time spent playing since the campaign started
+    pub fn campaign_time(&self) -> Duration {
+        Duration::from_secs_f64(self.campaign_time)
+    }
+
+    // The player dies once they fall below this virtual y coordinate
+    pub fn set_fall_limit(&mut self, fall_limit: f32) {
+        self.fall_limit = fall_limit;
+    }
+
+    // Events handled since the last call, in the order they were published.
+    // Only the last MAX_HANDLED_EVENTS are kept, so call it every frame.
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        self.handled_events.drain(..).collect()
+    }
+
+    pub fn subscribe(&mut self, subscriber: Box<dyn EventSubscriber + Send>) {
+        self.subscribers.push(subscriber);
+    }
+
+    // Advances the simulation by one TIMESTEP using the current player input
+    pub fn step(&mut self) {
+        self.s_update(TIMESTEP);
+        self.s_events();
+        self.s_collision();
+        self.s_events();
+
+        // Presses and releases only count for the frame they happened in
+        self.player_input_mut().clear_transitions();
+    }
+
+    pub fn s_update(&mut self, dt: f32) {
+        if self.game_state == GameState::CampaignComplete {
+            return;
+        }
+
+        self.campaign_time += f64::from(dt);
+
+        // Update entities
+        self.entity_manager.update(&mut self.entity_memory_pool);
+
+        // Player jump
+        let (jump_input_release, dash_input_press, input_x, input_y) = {
+            let player_input = self
+                .player
+                .get_component_mut::<CInput>(&mut self.entity_memory_pool)
+                .unwrap();
+
+            let mut input_x = 0;
+
+            if player_input.right.held {
+                input_x += 1;
+            }
+            if player_input.left.held {
+                input_x -= 1;
+            }
+
+            let mut input_y = 0;
+
+            if player_input.down.held {
+                input_y += 1;
+            }
+            if player_input.up.held {
+                input_y -= 1;
+            }
+
+            if player_input.space.pressed {
+                self.jump_input_timer = self.movement.jump_input_duration;
+            }
+
+            (
+                player_input.space.released,
+                player_input.dash.pressed,
+                input_x,
+                input_y,
+            )
+        };
+
+        // Dash, spending a charge only when it starts in the air
+        let grounded = self.player_transform().grounded;
+        if dash_input_press
+            && self.levels[self.level_index].dash
+            && self.dash_timer == 0.0
+            && self.dash_cooldown_timer == 0.0
+            && (grounded || self.dash_charges > 0)
+        {
+            // In the held direction, or straight ahead if none is held
+            let direction = if input_x == 0 && input_y == 0 {
+                Vector2::new(self.facing, 0.0)
+            } else {
+                Vector2::new(input_x as f32, input_y as f32)
+            };
+            let length = (direction.x * direction.x + direction.y * direction.y).sqrt();
+
+            self.dash_velocity = direction * (self.movement.dash_speed / length);
+            self.dash_timer = self.movement.dash_duration;
+            self.dash_cooldown_timer = self.movement.dash_duration + self.movement.dash_cooldown;
+            if !grounded {
+                self.dash_charges -= 1;
+            }
+            self.wall_jump_physics = false;
+            self.events.publish(GameEvent::PlayerDashed);
+        }
+        if input_x != 0 {
+            self.facing = input_x as f32;
+        }
+        let dashing = self.dash_timer > 0.0;
+
+        // Player physics
+        let (fell_off, wall_sliding) = {
+            let player_transform = self
+                .player
+                .get_component_mut::<CTransform>(&mut self.entity_memory_pool)
+                .unwrap();
+
+            // Gravity
+            player_transform.acceleration.y = self.movement.gravity;
+
+            // Jump, held in the buffer until a dash ends
+            if self.jump_input_timer > 0.0 && !dashing {
+                // Normal
+                if self.grounded_timer > 0.0 {
+                    player_transform.velocity.y = -self.movement.jump_speed;
+                    self.grounded_timer = 0.0;
+                    self.jump_input_timer = 0.0;
+                    self.events.publish(GameEvent::PlayerJumped);
+                }
+                // Wall jump
+                else if self.wall_contact_timer > 0.0 {
+                    player_transform.velocity.y = -self.movement.wall_jump_speed;
+                    player_transform.velocity.x =
+                        player_transform.max_speed * -self.wall_contact_side;
+
+                    self.wall_contact_timer = 0.0;
+                    self.grounded_timer = 0.0;
+                    self.jump_input_timer = 0.0;
+                    self.events.publish(GameEvent::WallJumped);
+                    self.wall_jump_physics = true;
+                }
+                // Air jump, only once coyote time and the wall have run out
+                else if self.air_jumps > 0 {
+                    player_transform.velocity.y = -self.movement.jump_speed;
+                    self.air_jumps -= 1;
+                    self.jump_input_timer = 0.0;
+                    self.wall_jump_physics = false;
+                    self.events.publish(GameEvent::AirJumped);
+                }
+            }
+            // Start falling
+            if jump_input_release && player_transform.velocity.y < 0.0 {
+                player_transform.velocity.y /= self.movement.jump_cut_divisor;
+            }
+
+            // Move
+            let desired_speed = input_x as f32 * player_transform.max_speed;
+
+            // Different scaler for acceleration and deceleration
+            let acceleration_scale = if input_x != 0 {
+                self.movement.acceleration_scale.x
+            } else {
+                self.movement.acceleration_scale.y
+            };
+
+            if !self.wall_jump_physics {
+                player_transform.acceleration.x =
+                    (desired_speed - player_transform.velocity.x) * acceleration_scale;
+            } else {
+                player_transform.acceleration.x = 0.0;
+                player_transform.velocity.x +=
+                    input_x as f32 * self.movement.wall_jump_air_acceleration * dt;
+                // Clamp velocity
+                if player_transform.velocity.x.abs() > player_transform.max_speed {
+                    player_transform.velocity.x =
+                        player_transform.max_speed * player_transform.velocity.x.signum();
+                }
+            }
+
+            // A dash ignores gravity and steering
+            if dashing {
+                player_transform.acceleration = Vector2::new(0.0, 0.0);
+                player_transform.velocity = self.dash_velocity;
+            }
+
+            // Wall slide
+            let wall_sliding = !dashing
+                && !player_transform.grounded
+                && player_transform.velocity.y >= 0.0
+                && (self.player_contacts.left_wall && input_x < 0
+                    || self.player_contacts.right_wall && input_x > 0);
+            if wall_sliding {
+                // Gravity only up to the slide speed, slowing a faster fall
+                // down to it
+                let slide_acceleration =
+                    (self.movement.wall_slide_speed - player_transform.velocity.y) / dt;
+                player_transform.acceleration.y =
+                    player_transform.acceleration.y.min(slide_acceleration);
+            }
+
+            // Update velocity and position
+            player_transform.update(dt);
+
+            // Leave the dash no faster than the player can run
+            if dashing && self.dash_timer <= dt + DASH_EPSILON {
+                let end_speed = player_transform.max_speed.min(self.movement.dash_speed);
+                player_transform.velocity =
+                    self.dash_velocity * (end_speed / self.movement.dash_speed);
+            }
+
+            (
+                player_transform.position.y > self.fall_limit - player_transform.half_size.y,
+                wall_sliding,
+            )
+        };
+
+        if wall_sliding != self.wall_sliding {
+            self.wall_sliding = wall_sliding;
+            self.events.publish(if wall_sliding {
+                GameEvent::WallSlideStarted
+            } else {
+                GameEvent::WallSlideEnded
+            });
+        }
+
+        if fell_off {
+            self.kill_player();
+        }
+
+        // Update jump timers. A press during a dash waits for it to end.
+        if !dashing {
+            self.jump_input_timer = (self.jump_input_timer - dt).max(0.0);
+        }
+        self.grounded_timer = (self.grounded_timer - dt).max(0.0);
+        self.wall_contact_timer = (self.wall_contact_timer - dt).max(0.0);
+        self.dash_timer -= dt;
+        if self.dash_timer < DASH_EPSILON {
+            self.dash_timer = 0.0;
+        }
+        self.dash_cooldown_timer = (self.dash_cooldown_timer - dt).max(0.0);
+    }
+
+    pub fn s_collision(&mut self) {
+        if self.game_state == GameState::CampaignComplete {
+            return;
+        }
+
+        let (player_position, player_prev_position, player_half_size) = {
+            let player_transform = self
+                .player
+                .get_component::<CTransform>(&self.entity_memory_pool)
+                .unwrap();
+
+            (
+                player_transform.position,
+                player_transform.prev_position,
+                player_transform.half_size,
+            )
+        };
+
+        // Copy the platform transforms out so the player can be moved while checking them
+        let platform_transforms: Vec<CTransform> = self
+            .entity_memory_pool
+            .query_by_tag::<CTransform>(EntityTag::Platform)
+            .map(|(_, platform_transform)| platform_transform.clone())
+            .collect();
+
+        let mut contacts = Contacts::default();
+
+        // Sweep the player from where they were to where physics moved them,
+        // stopping at the earliest platform and sliding along it
+        let mut swept_position = player_prev_position;
+        let mut remaining_delta = player_position - player_prev_position;
+
+        for _ in 0..collision::MAX_SWEEP_ITERATIONS {
+            let earliest_hit = platform_transforms
+                .iter()
+                .filter_map(|platform_transform| {
+                    collision::sweep_aabb(
+                        swept_position,
+                        remaining_delta,
+                        player_half_size,
+                        platform_transform.position,
+                        platform_transform.half_size,
+                    )
+                })
+                .min_by(|a, b| a.time.total_cmp(&b.time));
+
+            let Some(hit) = earliest_hit else {
+                swept_position += remaining_delta;
+                break;
+            };
+
+            swept_position = hit.position;
+            remaining_delta *= 1.0 - hit.time;
+            contacts.add(hit.normal);
+
+            // Keep moving along the surface, but not into it
+            if hit.normal.x != 0.0 {
+                remaining_delta.x = 0.0;
+            } else {
+                remaining_delta.y = 0.0;
+            }
+        }
+
+        // Push the player out of platforms they already overlapped before moving,
+        // e.g. one that was loaded on top of them. Each push is applied before
+        // checking the next platform, and the pass repeats in case a push moved
+        // the player into a platform that was already checked.
+        let mut resolved_position = swept_position;
+
+        for _ in 0..collision::MAX_RESOLVE_ITERATIONS {
+            let mut pushed = false;
+
+            for platform_transform in platform_transforms.iter() {
+                let penetration = platform_transform.get_penetration(
+                    resolved_position,
+                    player_prev_position,
+                    player_half_size,
+                );
+
+                if let Some(penetration) = penetration {
+                    resolved_position += penetration.normal * penetration.depth;
+                    contacts.add(penetration.normal);
+                    pushed = true;
+                }
+            }
+
+            if !pushed {
+                break;
+            }
+        }
+
+        // Surfaces the player ends the frame resting against
+        for platform_transform in platform_transforms.iter() {
+            if let Some(normal) = collision::touching_normal(
+                resolved_position,
+                player_half_size,
+                platform_transform.position,
+                platform_transform.half_size,
+            ) {
+                contacts.add(normal);
+            }
+        }
+
+        self.player_contacts = contacts;
+
+        {
+            let player_transform = self
+                .player
+                .get_component_mut::<CTransform>(&mut self.entity_memory_pool)
+                .unwrap();
+
+            let was_grounded = player_transform.grounded;
+            player_transform.grounded = false;
+
+            player_transform.position = resolved_position;
+
+            // Resting on the floor counts too, e.g. during a dash along it
+            if contacts.floor && player_transform.velocity.y >= 0.0 {
+                if !was_grounded {
+                    self.events.publish(GameEvent::LandedOnPlatform);
+                }
+                player_transform.grounded = true;
+                player_transform.velocity.y = 0.0;
+                self.grounded_timer = self.movement.grounded_duration;
+                self.dash_charges = self.movement.air_dashes;
+                self.air_jumps = self.max_air_jumps;
+                self.wall_jump_physics = false;
+            }
+            if contacts.ceiling && player_transform.velocity.y < 0.0 {
+                player_transform.velocity.y = 0.0;
+            }
+            if contacts.left_wall && player_transform.velocity.x < 0.0
+                || contacts.right_wall && player_transform.velocity.x > 0.0
+            {
+                player_transform.velocity.x = 0.0;
+            }
+            if contacts.left_wall || contacts.right_wall {
+                self.wall_contact_timer = self.movement.wall_contact_duration;
+                self.wall_contact_side = if contacts.right_wall { 1.0 } else { -1.0 };
+            }
+        }
+
+        // Hazards and the goal are checked along the whole move too, so they
+        // can't be skipped over
+        let player_hit_hazard = self
+            .entity_memory_pool
+            .query_by_tag::<CTransform>(EntityTag::Hazard)
+            .any(|(_, hazard_transform)| {
+                player_reached(
+                    hazard_transform,
+                    player_prev_position,
+                    resolved_position,
+                    player_half_size,
+                )
+            });
+
+        if player_hit_hazard {
+            self.kill_player();
+            return;
+        }
+
+        let collected: Vec<(Entity, u32)> = self
+            .entity_memory_pool
+            .query2_by_tag::<CTransform, CPickup>(EntityTag::Pickup)
+            .filter(|(_, pickup_transform, _)| {
+                player_reached(
+                    pickup_transform,
+                    player_prev_position,
+                    resolved_position,
+                    player_half_size,
+                )
+            })
+            .map(|(pickup, _, pickup_component)| (pickup, pickup_component.air_jumps))
+            .collect();
+
+        for (pickup, air_jumps) in collected {
+            self.max_air_jumps += air_jumps;
+            self.air_jumps += air_jumps;
+            self.entity_manager.destroy_entity(pickup);
+            self.events.publish(GameEvent::PickupCollected(pickup));
+        }
+
+        let goal_transform = self
+            .goal
+            .get_component::<CTransform>(&self.entity_memory_pool)
+            .unwrap();
+
+        if player_reached(
+            goal_transform,
+            player_prev_position,
+            resolved_position,
+            player_half_size,
+        ) {
+            self.events.publish(GameEvent::GoalReached);
+        }
+    }
+
+    // Hands each published event to the stats and subscribers, then applies
+    // the world's own reactions, which may publish more
+    pub fn s_events(&mut self) {
+        while !self.events.is_empty() {
+            for event in self.events.take() {
+                self.stats.on_event(event);
+                for subscriber in self.subscribers.iter_mut() {
+                    subscriber.on_event(event);
+                }
+                if self.handled_events.len() == MAX_HANDLED_EVENTS {
+                    self.handled_events.pop_front();
+                }
+                self.handled_events.push_back(event);
+
+                match event {
+                    GameEvent::PlayerDied => self.respawn_player(),
+                    GameEvent::GoalReached => self.complete_level(),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn complete_level(&mut self) {
+        self.events.publish(GameEvent::LevelCompleted);
+
+        // Last level
+        if self.level_index + 1 >= self.levels.len() {
+            self.complete_campaign();
+            return;
+        }
+
+        self.level_index += 1;
+
+        self.load_level(self.level_index);
+
+        self.respawn_player();
+    }
+
+    pub fn complete_campaign(&mut self) {
+        self.game_state = GameState::CampaignComplete;
+        self.events.publish(GameEvent::CampaignCompleted);
+    }
+
+    pub fn restart_campaign(&mut self) {
+        self.game_state = GameState::Playing;
+        self.stats = Stats::default();
+        self.campaign_time = 0.0;
+
+        // Keys held when the campaign ended are stale
+        self.player_input_mut().reset();
+
+        self.level_index = 0;
+        self.load_level(self.level_index);
+        self.respawn_player();
+        self.s_events();
+    }
+
+    // The player respawns when the event is handled, at the end of the system
+    pub fn kill_player(&mut self) {
+        self.events.publish(GameEvent::PlayerDied);
+    }
+
+    pub fn respawn_player(&mut self) {
+        let player_transform = self
+            .player
+            .get_component_mut::<CTransform>(&mut self.entity_memory_pool)
+            .unwrap();
+
+        player_transform.position = self.levels[self.level_index].player_start;
+        player_transform.prev_position = player_transform.position;
+        player_transform.velocity = Vector2::new(0.0, 0.0);
+        // Nothing is touched at the spawn point until collision says so
+        player_transform.grounded = false;
+        self.player_contacts = Contacts::default();
+
+        self.grounded_timer = 0.0;
+        self.jump_input_timer = 0.0;
+        self.wall_contact_timer = 0.0;
+        self.wall_jump_physics = false;
+        self.dash_timer = 0.0;
+        self.dash_cooldown_timer = 0.0;
+        self.dash_charges = self.movement.air_dashes;
+        self.air_jumps = self.max_air_jumps;
+
+        if self.wall_sliding {
+            self.wall_sliding = false;
+            self.events.publish(GameEvent::WallSlideEnded);
+        }
+    }
+
+    pub fn load_level(&mut self, level_index: usize) {
+        let level = &self.levels[level_index];
+
+        let movement_name = level.movement.as_deref().unwrap_or(DEFAULT_MOVEMENT);
+        self.movement = *self.movement_profiles.get(movement_name).unwrap();
+        self.dash_charges = self.movement.air_dashes;
+        self.max_air_jumps = level.air_jumps;
+        self.air_jumps = self.max_air_jumps;
+
+        // Player
+        {
+            let player_transform = self
+                .player
+                .get_component_mut::<CTransform>(&mut self.entity_memory_pool)
+                .unwrap();
+
+            player_transform.max_speed = self.movement.max_speed;
+            player_transform.position = level.player_start;
+            player_transform.prev_position = level.player_start;
+            player_transform.velocity = Vector2::new(0.0, 0.0);
+        }
+
+        // Goal
+        {
+            let goal_transform = self
+                .goal
+                .get_component_mut::<CTransform>(&mut self.entity_memory_pool)
+                .unwrap();
+
+            goal_transform.position = level.goal;
+            goal_transform.prev_position = level.goal;
+            goal_transform.velocity = Vector2::new(0.0, 0.0);
+        }
+
+        // Destroy the previous level's platforms and other entities
+        for entity in self.level_entities.drain(..) {
+            self.entity_manager.destroy_entity(entity);
+        }
+        self.entity_manager.update(&mut self.entity_memory_pool);
+
+        // Create the new ones
+        for platform_rect in level.platforms.iter() {
+            self.level_entities.push(self.prefabs.spawn(
+                "platform",
+                platform_rect.position,
+                Some(platform_rect.size),
+                &mut self.entity_manager,
+                &mut self.entity_memory_pool,
+            ));
+        }
+        for level_entity in level.entities.iter() {
+            self.level_entities.push(self.prefabs.spawn(
+                &level_entity.prefab,
+                level_entity.position,
+                level_entity.size,
+                &mut self.entity_manager,
+                &mut self.entity_memory_pool,
+            ));
+        }
+        // Make the new entities visible to systems straight away
+        self.entity_manager.update(&mut self.entity_memory_pool);
+
+        self.events.publish(GameEvent::LevelLoaded);
+    }
+}
+
+// Whether a box moving from `from` to `to` touched the target at any point
+fn player_reached(
+    target: &CTransform,
+    from: Vector2<f32>,
+    to: Vector2<f32>,
+    half_size: Vector2<f32>,
+) -> bool {
+    let overlap = target.get_overlap(to, half_size);
+    let hit = collision::sweep_aabb(
+        from,
+        to - from,
+        half_size,
+        target.position,
+        target.half_size,
+    );
+
+    (overlap.x > 0.0 && overlap.y > 0.0) || hit.is_some()
+}
@@ -0,0 +1,258 @@
+mod common;
+
+use common::{level, platform};
+use sfml::system::Vector2;
+use window_platformer::{
+    collision::{sweep_aabb, Contacts},
+    components::CTransform,
+    world::World,
+};
+
+const PLAYER_HALF_SIZE: Vector2<f32> = Vector2::new(12.5, 25.0);
+
+#[test]
+fn sweep_stops_at_thin_wall() {
+    // A 10 px wall entirely skipped over by a 200 px move
+    let hit = sweep_aabb(
+        Vector2::new(100.0, 500.0),
+        Vector2::new(200.0, 0.0),
+        PLAYER_HALF_SIZE,
+        Vector2::new(200.0, 500.0),
+        Vector2::new(5.0, 100.0),
+    )
+    .unwrap();
+
+    assert_eq!(hit.normal, Vector2::new(-1.0, 0.0));
+    assert_eq!(hit.position.x, 200.0 - 5.0 - 12.5);
+    assert!((hit.time - (82.5 / 200.0)).abs() < 1e-6);
+}
+
+#[test]
+fn sweep_stops_on_thin_floor() {
+    let hit = sweep_aabb(
+        Vector2::new(500.0, 0.0),
+        Vector2::new(0.0, 1000.0),
+        PLAYER_HALF_SIZE,
+        Vector2::new(500.0, 500.0),
+        Vector2::new(100.0, 2.0),
+    )
+    .unwrap();
+
+    assert_eq!(hit.normal, Vector2::new(0.0, -1.0));
+    assert_eq!(hit.position.y, 500.0 - 2.0 - 25.0);
+}
+
+#[test]
+fn sweep_misses_when_moving_away_or_short() {
+    let target_position = Vector2::new(200.0, 500.0);
+    let target_half_size = Vector2::new(5.0, 100.0);
+
+    let away = sweep_aabb(
+        Vector2::new(100.0, 500.0),
+        Vector2::new(-200.0, 0.0),
+        PLAYER_HALF_SIZE,
+        target_position,
+        target_half_size,
+    );
+    let short = sweep_aabb(
+        Vector2::new(100.0, 500.0),
+        Vector2::new(50.0, 0.0),
+        PLAYER_HALF_SIZE,
+        target_position,
+        target_half_size,
+    );
+
+    assert_eq!(away, None);
+    assert_eq!(short, None);
+}
+
+#[test]
+fn sweep_exact_corner_hit_lands_on_top() {
+    // Moving diagonally so both edges are reached at the same moment
+    let hit = sweep_aabb(
+        Vector2::new(0.0, 0.0),
+        Vector2::new(100.0, 100.0),
+        Vector2::new(10.0, 10.0),
+        Vector2::new(100.0, 100.0),
+        Vector2::new(40.0, 40.0),
+    )
+    .unwrap();
+
+    assert_eq!(hit.normal, Vector2::new(0.0, -1.0));
+    assert!((hit.time - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn sweep_grazing_a_corner_is_not_a_hit() {
+    // Slides exactly along the top face without ever overlapping it
+    let hit = sweep_aabb(
+        Vector2::new(0.0, 50.0),
+        Vector2::new(200.0, 0.0),
+        Vector2::new(10.0, 10.0),
+        Vector2::new(100.0, 100.0),
+        Vector2::new(40.0, 40.0),
+    );
+
+    assert_eq!(hit, None);
+}
+
+#[test]
+fn overlap_is_measured_in_sub_pixels() {
+    let mut platform = CTransform::new();
+    platform.position = Vector2::new(100.0, 100.0);
+    platform.half_size = Vector2::new(50.0, 10.0);
+
+    let overlap = platform.get_overlap(Vector2::new(100.0, 65.25), PLAYER_HALF_SIZE);
+
+    assert_eq!(overlap, Vector2::new(62.5, 0.25));
+}
+
+#[test]
+fn penetration_pushes_out_along_the_entry_axis() {
+    let mut platform = CTransform::new();
+    platform.position = Vector2::new(100.0, 100.0);
+    platform.half_size = Vector2::new(50.0, 10.0);
+
+    // Came down from above, so it is pushed back up
+    let from_above = platform
+        .get_penetration(
+            Vector2::new(140.0, 65.5),
+            Vector2::new(140.0, 60.0),
+            PLAYER_HALF_SIZE,
+        )
+        .unwrap();
+    assert_eq!(from_above.normal, Vector2::new(0.0, -1.0));
+    assert_eq!(from_above.depth, 0.5);
+
+    // Came in from the side, so it is pushed back out sideways
+    let from_the_side = platform
+        .get_penetration(
+            Vector2::new(161.0, 100.0),
+            Vector2::new(165.0, 100.0),
+            PLAYER_HALF_SIZE,
+        )
+        .unwrap();
+    assert_eq!(from_the_side.normal, Vector2::new(1.0, 0.0));
+    assert_eq!(from_the_side.depth, 1.5);
+
+    // Exactly touching is not penetrating
+    let touching = platform.get_penetration(
+        Vector2::new(100.0, 65.0),
+        Vector2::new(100.0, 65.0),
+        PLAYER_HALF_SIZE,
+    );
+    assert_eq!(touching, None);
+}
+
+#[test]
+fn fast_falling_player_does_not_tunnel_through_thin_platform() {
+    let mut world = World::new(vec![level(
+        vec![platform(960.0, 600.0, 400.0, 10.0)],
+        Vector2::new(960.0, 300.0),
+    )]);
+
+    // 100 px per tick, ten times the platform's thickness
+    world.player_transform_mut().velocity.y = 6000.0;
+    for _ in 0..30 {
+        world.step();
+    }
+
+    let player_transform = world.player_transform();
+    assert!(player_transform.grounded);
+    assert_eq!(player_transform.position.y, 600.0 - 5.0 - 25.0);
+    assert_eq!(world.deaths(), 0);
+}
+
+#[test]
+fn fast_player_does_not_tunnel_through_thin_wall() {
+    let mut world = World::new(vec![level(
+        vec![
+            platform(960.0, 600.0, 1000.0, 50.0),
+            platform(1100.0, 400.0, 10.0, 300.0),
+        ],
+        Vector2::new(900.0, 550.0),
+    )]);
+
+    // Enough that one tick would carry the player well past the wall
+    world.player_transform_mut().velocity.x = 30000.0;
+    world.step();
+
+    let player_transform = world.player_transform();
+    assert_eq!(player_transform.position.x, 1100.0 - 5.0 - 12.5);
+    assert_eq!(player_transform.velocity.x, 0.0);
+}
+
+#[test]
+fn player_in_a_corner_reports_floor_and_wall() {
+    let mut world = World::new(vec![level(
+        vec![
+            platform(960.0, 600.0, 1000.0, 50.0),
+            platform(1100.0, 400.0, 10.0, 300.0),
+        ],
+        Vector2::new(1082.5, 550.0),
+    )]);
+
+    for _ in 0..10 {
+        world.player_transform_mut().velocity.x = 600.0;
+        world.step();
+    }
+
+    assert_eq!(
+        world.player_contacts(),
+        Contacts {
+            floor: true,
+            right_wall: true,
+            ..Default::default()
+        }
+    );
+    let player_transform = world.player_transform();
+    assert!(player_transform.grounded);
+    assert_eq!(player_transform.position, Vector2::new(1082.5, 550.0));
+}
+
+#[test]
+fn player_crosses_the_seam_between_two_platforms() {
+    // Two floors whose top edges line up and meet at x = 950
+    let mut world = World::new(vec![level(
+        vec![
+            platform(700.0, 600.0, 500.0, 50.0),
+            platform(1200.0, 600.0, 500.0, 50.0),
+        ],
+        Vector2::new(930.0, 550.0),
+    )]);
+
+    for _ in 0..20 {
+        world.player_transform_mut().velocity.x = 600.0;
+        world.step();
+
+        let contacts = world.player_contacts();
+        assert!(contacts.floor);
+        assert!(!contacts.left_wall && !contacts.right_wall);
+        assert_eq!(world.player_transform().position.y, 550.0);
+    }
+
+    assert!(world.player_transform().position.x > 950.0);
+    assert!(world.player_transform().velocity.x > 0.0);
+}
+
+#[test]
+fn jumping_into_a_low_ceiling_reports_it() {
+    let mut world = World::new(vec![level(
+        vec![
+            platform(960.0, 600.0, 1000.0, 50.0),
+            // Bottom edge 10 px above the player's head
+            platform(960.0, 505.0, 1000.0, 20.0),
+        ],
+        Vector2::new(960.0, 550.0),
+    )]);
+    for _ in 0..10 {
+        world.step();
+    }
+
+    world.player_input_mut().space.press();
+    world.step();
+
+    assert!(world.player_contacts().ceiling);
+    assert_eq!(world.player_transform().position.y, 515.0 + 25.0);
+    assert_eq!(world.player_transform().velocity.y, 0.0);
+}
@@ -0,0 +1,58 @@
+// Level fixtures shared by the integration tests. Each test crate uses only
+// some of them.
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use sfml::system::Vector2;
+use window_platformer::{
+    levels::{Level, PlatformRect},
+    world::World,
+};
+
+// A file or directory in the shipped assets, wherever the tests are run from
+pub fn asset_path(relative: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("assets")
+        .join(relative)
+}
+
+pub fn platform(x: f32, y: f32, width: f32, height: f32) -> PlatformRect {
+    PlatformRect {
+        position: Vector2::new(x, y),
+        size: Vector2::new(width, height),
+    }
+}
+
+// The player starts at `player_start` among `platforms`, with the goal out of
+// reach and every optional field left at its default
+pub fn level(platforms: Vec<PlatformRect>, player_start: Vector2<f32>) -> Level {
+    Level {
+        platforms,
+        player_start,
+        goal: Vector2::new(100.0, 100.0),
+        ..Level::default()
+    }
+}
+
+// A wide floor whose top edge is at y = 575, with the goal out of reach
+pub fn floor_level() -> Level {
+    level(
+        vec![platform(960.0, 600.0, 1000.0, 50.0)],
+        Vector2::new(960.0, 400.0),
+    )
+}
+
+// A tall wall with no floor, its left face at x = 1075
+pub fn wall_level() -> Level {
+    level(
+        vec![platform(1100.0, 400.0, 50.0, 600.0)],
+        Vector2::new(1050.0, 200.0),
+    )
+}
+
+pub fn step_frames(world: &mut World, frames: u32) {
+    for _ in 0..frames {
+        world.step();
+    }
+}
@@ -0,0 +1,410 @@
+use std::any::Any;
+
+use sfml::system::Vector2;
+use window_platformer::{
+    components::{CHealth, CInput, CTransform, Component},
+    entities::{
+        command_buffer::CommandBuffer,
+        entity::Entity,
+        entity_manager::EntityManager,
+        entity_memory_pool::{EntityMemoryPool, PoolFullError},
+        EntityTag,
+    },
+};
+
+#[test]
+fn stale_handle_does_not_alias_a_reused_slot() {
+    let mut entity_memory_pool = EntityMemoryPool::new(4);
+    let mut entity_manager = EntityManager::new();
+
+    let mut platform = entity_manager.add_entity(EntityTag::Platform, &mut entity_memory_pool);
+    platform.add_component(&mut entity_manager, CTransform::new());
+    entity_manager.update(&mut entity_memory_pool);
+    let stale_platform = platform;
+    entity_manager.destroy_entity(platform);
+    entity_manager.update(&mut entity_memory_pool);
+
+    // The next entity reuses the freed slot under a new generation
+    let mut goal = entity_manager.add_entity(EntityTag::Goal, &mut entity_memory_pool);
+    goal.add_component(
+        &mut entity_manager,
+        CTransform::new().with_position(Vector2::new(100.0, 0.0)),
+    );
+    entity_manager.update(&mut entity_memory_pool);
+
+    assert_eq!(goal.id(), stale_platform.id());
+    assert_ne!(goal, stale_platform);
+    assert!(goal.is_alive(&entity_memory_pool));
+    assert!(!stale_platform.is_alive(&entity_memory_pool));
+    assert!(stale_platform
+        .get_component::<CTransform>(&entity_memory_pool)
+        .is_none());
+    assert_eq!(entity_memory_pool.get_tag(stale_platform), None);
+
+    // Destroying through the stale handle leaves the new entity alone
+    let mut stale_platform = stale_platform;
+    stale_platform.destroy(&mut entity_manager);
+    entity_manager.update(&mut entity_memory_pool);
+    assert!(goal.is_alive(&entity_memory_pool));
+    assert_eq!(
+        goal.get_component::<CTransform>(&entity_memory_pool)
+            .unwrap()
+            .position
+            .x,
+        100.0
+    );
+}
+
+#[test]
+fn handle_from_a_bigger_pool_does_not_resolve() {
+    let mut entity_memory_pool = EntityMemoryPool::new(2);
+    let mut entity_manager = EntityManager::new();
+    let mut goal = entity_manager.add_entity(EntityTag::Goal, &mut entity_memory_pool);
+    goal.add_component(&mut entity_manager, CTransform::new());
+    entity_manager.update(&mut entity_memory_pool);
+
+    let foreign = Entity::new(100, 0);
+    assert!(!foreign.is_alive(&entity_memory_pool));
+    assert!(!entity_memory_pool.exists(foreign));
+    assert!(foreign
+        .get_component::<CTransform>(&entity_memory_pool)
+        .is_none());
+    assert_eq!(entity_memory_pool.get_tag(foreign), None);
+    assert_eq!(entity_memory_pool.get_mask(foreign), 0);
+
+    // Destroying it is a no-op rather than a panic
+    entity_manager.destroy_entity(foreign);
+    entity_manager.update(&mut entity_memory_pool);
+    assert!(goal.is_alive(&entity_memory_pool));
+}
+
+#[test]
+fn full_pool_reports_an_error_and_recycles_freed_slots() {
+    let mut entity_memory_pool = EntityMemoryPool::new(2);
+    let mut entity_manager = EntityManager::new();
+
+    let mut first = entity_manager
+        .try_add_entity(EntityTag::Platform, &mut entity_memory_pool)
+        .unwrap();
+    let second = entity_manager
+        .try_add_entity(EntityTag::Platform, &mut entity_memory_pool)
+        .unwrap();
+    assert_eq!((first.id(), second.id()), (0, 1));
+    assert_eq!(
+        entity_manager.try_add_entity(EntityTag::Platform, &mut entity_memory_pool),
+        Err(PoolFullError { capacity: 2 })
+    );
+
+    entity_manager.update(&mut entity_memory_pool);
+    first.destroy(&mut entity_manager);
+    entity_manager.update(&mut entity_memory_pool);
+    let third = entity_manager
+        .try_add_entity(EntityTag::Goal, &mut entity_memory_pool)
+        .unwrap();
+    assert_eq!(third.id(), first.id());
+}
+
+#[test]
+fn growable_pool_adds_slots_when_full() {
+    let mut entity_memory_pool = EntityMemoryPool::growable(1);
+    let mut entity_manager = EntityManager::new();
+
+    let entities: Vec<_> = (0..5)
+        .map(|_| {
+            entity_manager
+                .try_add_entity(EntityTag::Platform, &mut entity_memory_pool)
+                .unwrap()
+        })
+        .collect();
+    entity_manager.update(&mut entity_memory_pool);
+
+    let ids: Vec<_> = entities.iter().map(|entity| entity.id()).collect();
+    assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+    assert!(entity_memory_pool.get_entity_count() >= 5);
+    assert!(entities
+        .iter()
+        .all(|entity| entity.is_alive(&entity_memory_pool)));
+}
+
+#[derive(Default)]
+struct CScore {
+    active: bool,
+    score: u32,
+}
+
+impl Component for CScore {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn is_active(&self) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+
+    fn reset(&mut self) {
+        self.active = true;
+        self.score = 0;
+    }
+}
+
+#[test]
+fn registered_components_can_be_attached() {
+    let mut entity_memory_pool = EntityMemoryPool::growable(1);
+    let mut entity_manager = EntityManager::new();
+    let mut player = entity_manager.add_entity(EntityTag::Player, &mut entity_memory_pool);
+
+    // Built-in components that were never stored before
+    player.add_component(&mut entity_manager, CHealth::new(3.0));
+    entity_manager.update(&mut entity_memory_pool);
+    assert_eq!(
+        player
+            .get_component::<CHealth>(&entity_memory_pool)
+            .unwrap()
+            .health,
+        3.0
+    );
+
+    // A component the library knows nothing about
+    assert!(!entity_memory_pool.is_registered::<CScore>());
+    entity_memory_pool.register_component::<CScore>();
+    player.add_component(&mut entity_manager, CScore::default());
+    entity_manager.update(&mut entity_memory_pool);
+    player
+        .get_component_mut::<CScore>(&mut entity_memory_pool)
+        .unwrap()
+        .score = 10;
+
+    // Storage for it grows along with the pool
+    let mut goal = entity_manager.add_entity(EntityTag::Goal, &mut entity_memory_pool);
+    goal.add_component(&mut entity_manager, CScore::default());
+    entity_manager.update(&mut entity_memory_pool);
+
+    assert_eq!(
+        player
+            .get_component::<CScore>(&entity_memory_pool)
+            .unwrap()
+            .score,
+        10
+    );
+    assert!(goal
+        .get_component::<CScore>(&entity_memory_pool)
+        .unwrap()
+        .is_active());
+}
+
+#[test]
+fn components_are_only_present_once_added() {
+    let mut entity_memory_pool = EntityMemoryPool::new(2);
+    let mut entity_manager = EntityManager::new();
+    let mut goal = entity_manager.add_entity(EntityTag::Goal, &mut entity_memory_pool);
+    goal.add_component(&mut entity_manager, CTransform::new());
+    entity_manager.update(&mut entity_memory_pool);
+
+    assert!(goal.has_component::<CTransform>(&entity_memory_pool));
+    assert!(!goal.has_component::<CInput>(&entity_memory_pool));
+    assert!(goal.get_component::<CInput>(&entity_memory_pool).is_none());
+
+    goal.remove_component::<CTransform>(&mut entity_manager);
+    entity_manager.update(&mut entity_memory_pool);
+    assert!(!goal.has_component::<CTransform>(&entity_memory_pool));
+    assert!(goal
+        .get_component::<CTransform>(&entity_memory_pool)
+        .is_none());
+
+    // A recycled slot starts without the previous entity's components
+    goal.add_component(&mut entity_manager, CTransform::new());
+    goal.destroy(&mut entity_manager);
+    entity_manager.update(&mut entity_memory_pool);
+    let platform = entity_manager.add_entity(EntityTag::Platform, &mut entity_memory_pool);
+    entity_manager.update(&mut entity_memory_pool);
+    assert_eq!(platform.id(), goal.id());
+    assert_eq!(entity_memory_pool.get_mask(platform), 0);
+    assert!(!platform.has_component::<CTransform>(&entity_memory_pool));
+}
+
+#[test]
+fn queries_yield_only_matching_live_entities() {
+    let mut entity_memory_pool = EntityMemoryPool::new(8);
+    let mut entity_manager = EntityManager::new();
+
+    let mut platforms = Vec::new();
+    for x in [100.0, 200.0, 300.0] {
+        let mut platform = entity_manager.add_entity(EntityTag::Platform, &mut entity_memory_pool);
+        platform.add_component(
+            &mut entity_manager,
+            CTransform::new().with_position(Vector2::new(x, 0.0)),
+        );
+        platforms.push(platform);
+    }
+    let mut player = entity_manager.add_entity(EntityTag::Player, &mut entity_memory_pool);
+    player.add_component(&mut entity_manager, CTransform::new());
+    // Tagged as a platform but without a transform
+    entity_manager.add_entity(EntityTag::Platform, &mut entity_memory_pool);
+    entity_manager.update(&mut entity_memory_pool);
+    platforms[1].destroy(&mut entity_manager);
+    entity_manager.update(&mut entity_memory_pool);
+
+    for (_, platform_transform) in
+        entity_memory_pool.query_by_tag_mut::<CTransform>(EntityTag::Platform)
+    {
+        platform_transform.position.y = 50.0;
+    }
+
+    let found: Vec<_> = entity_memory_pool
+        .query_by_tag::<CTransform>(EntityTag::Platform)
+        .map(|(entity, transform)| (entity, transform.position.x, transform.position.y))
+        .collect();
+    assert_eq!(
+        found,
+        vec![(platforms[0], 100.0, 50.0), (platforms[2], 300.0, 50.0)]
+    );
+
+    assert_eq!(entity_memory_pool.query::<CTransform>().count(), 3);
+    assert_eq!(
+        player
+            .get_component::<CTransform>(&entity_memory_pool)
+            .unwrap()
+            .position
+            .y,
+        0.0
+    );
+}
+
+#[test]
+fn two_component_queries_need_both() {
+    let mut entity_memory_pool = EntityMemoryPool::new(8);
+    let mut entity_manager = EntityManager::new();
+
+    let mut player = entity_manager.add_entity(EntityTag::Player, &mut entity_memory_pool);
+    player.add_component(&mut entity_manager, CTransform::new());
+    player.add_component(&mut entity_manager, CInput::new());
+    let mut platform = entity_manager.add_entity(EntityTag::Platform, &mut entity_memory_pool);
+    platform.add_component(&mut entity_manager, CTransform::new());
+    // Input but no transform
+    let mut enemy = entity_manager.add_entity(EntityTag::Enemy, &mut entity_memory_pool);
+    enemy.add_component(&mut entity_manager, CInput::new());
+    let mut other_player = entity_manager.add_entity(EntityTag::Player, &mut entity_memory_pool);
+    other_player.add_component(&mut entity_manager, CTransform::new());
+    other_player.add_component(&mut entity_manager, CInput::new());
+    other_player.remove_component::<CInput>(&mut entity_manager);
+    entity_manager.update(&mut entity_memory_pool);
+
+    let found: Vec<_> = entity_memory_pool
+        .query2::<CTransform, CInput>()
+        .map(|(entity, _, _)| entity)
+        .collect();
+    assert_eq!(found, vec![player]);
+    assert_eq!(
+        entity_memory_pool
+            .query2_by_tag::<CInput, CTransform>(EntityTag::Player)
+            .count(),
+        1
+    );
+    assert_eq!(
+        entity_memory_pool
+            .query2_by_tag::<CTransform, CInput>(EntityTag::Platform)
+            .count(),
+        0
+    );
+    // Never registered, so nothing has it
+    assert_eq!(entity_memory_pool.query2::<CTransform, CScore>().count(), 0);
+}
+
+#[test]
+fn added_components_start_from_the_given_value() {
+    let mut entity_memory_pool = EntityMemoryPool::new(2);
+    let mut entity_manager = EntityManager::new();
+    let mut player = entity_manager.add_entity(EntityTag::Player, &mut entity_memory_pool);
+
+    player.add_component(
+        &mut entity_manager,
+        CTransform::new()
+            .with_position(Vector2::new(960.0, 540.0))
+            .with_size(Vector2::new(25.0, 50.0))
+            .with_max_speed(900.0),
+    );
+    entity_manager.update(&mut entity_memory_pool);
+
+    let player_transform = player
+        .get_component_mut::<CTransform>(&mut entity_memory_pool)
+        .unwrap();
+    assert!(player_transform.is_active());
+    assert_eq!(player_transform.prev_position, Vector2::new(960.0, 540.0));
+    assert_eq!(player_transform.half_size, Vector2::new(12.5, 25.0));
+    assert_eq!(player_transform.max_speed, 900.0);
+
+    // Resetting goes back to the defaults new() uses, not to zero
+    player_transform.grounded = true;
+    player_transform.reset();
+    assert!(!player_transform.grounded);
+    assert_eq!(player_transform.max_speed, CTransform::new().max_speed);
+    assert_eq!(player_transform.scale, 1.0);
+}
+
+#[test]
+fn spawn_then_destroy_in_the_same_frame_is_never_seen() {
+    let mut entity_memory_pool = EntityMemoryPool::new(4);
+    let mut entity_manager = EntityManager::new();
+
+    let mut bullet = entity_manager.add_entity(EntityTag::Bullet, &mut entity_memory_pool);
+    bullet.add_component(&mut entity_manager, CTransform::new());
+
+    // Reserved but not spawned: invisible to systems
+    assert!(entity_memory_pool.exists(bullet));
+    assert!(!bullet.is_alive(&entity_memory_pool));
+    assert_eq!(entity_memory_pool.query::<CTransform>().count(), 0);
+
+    entity_manager.destroy_entity(bullet);
+    entity_manager.update(&mut entity_memory_pool);
+
+    assert!(!entity_memory_pool.exists(bullet));
+    assert!(entity_manager.get_all_entities().is_empty());
+    assert_eq!(entity_manager.get_entity_count_by_tag(EntityTag::Bullet), 0);
+    assert_eq!(entity_memory_pool.query::<CTransform>().count(), 0);
+
+    // Its slot is free again
+    let enemy = entity_manager.add_entity(EntityTag::Enemy, &mut entity_memory_pool);
+    assert_eq!(enemy.id(), bullet.id());
+}
+
+#[test]
+fn structural_changes_wait_for_update() {
+    let mut entity_memory_pool = EntityMemoryPool::new(4);
+    let mut entity_manager = EntityManager::new();
+    let enemy = entity_manager.add_entity(EntityTag::Enemy, &mut entity_memory_pool);
+    entity_manager.update(&mut entity_memory_pool);
+
+    entity_manager.add_component(enemy, CHealth::new(3.0));
+    assert!(!enemy.has_component::<CHealth>(&entity_memory_pool));
+    entity_manager.update(&mut entity_memory_pool);
+    assert!(enemy.has_component::<CHealth>(&entity_memory_pool));
+
+    entity_manager.remove_component::<CHealth>(enemy);
+    entity_manager.destroy_entity(enemy);
+    assert!(enemy.has_component::<CHealth>(&entity_memory_pool));
+    assert_eq!(entity_manager.get_entity_count_by_tag(EntityTag::Enemy), 1);
+    entity_manager.update(&mut entity_memory_pool);
+    assert!(!enemy.is_alive(&entity_memory_pool));
+    assert_eq!(entity_manager.get_entity_count_by_tag(EntityTag::Enemy), 0);
+
+    // A buffer can also be filled and flushed on its own
+    let mut commands = CommandBuffer::new();
+    let goal = entity_memory_pool.reserve_entity(EntityTag::Goal);
+    commands.spawn(goal, EntityTag::Goal);
+    commands.add_component(goal, CTransform::new());
+    assert_eq!(
+        commands.flush(&mut entity_memory_pool),
+        vec![(goal, EntityTag::Goal)]
+    );
+    assert!(commands.is_empty());
+    assert!(goal.is_alive(&entity_memory_pool));
+    assert!(goal.has_component::<CTransform>(&entity_memory_pool));
+}
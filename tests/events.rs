@@ -0,0 +1,116 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use common::{floor_level, wall_level};
+use sfml::system::Vector2;
+use window_platformer::{
+    events::{EventSubscriber, GameEvent},
+    world::{World, MAX_HANDLED_EVENTS},
+};
+
+// Records everything it is handed, shared so the test can look at it
+struct Recorder {
+    events: Arc<Mutex<Vec<GameEvent>>>,
+}
+
+impl EventSubscriber for Recorder {
+    fn on_event(&mut self, event: GameEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+#[test]
+fn subscribers_see_landing_once() {
+    let mut world = World::new(vec![floor_level()]);
+    let recorded = Arc::new(Mutex::new(Vec::new()));
+    world.subscribe(Box::new(Recorder {
+        events: recorded.clone(),
+    }));
+
+    for _ in 0..120 {
+        world.step();
+    }
+
+    let events = world.drain_events();
+    assert_eq!(*recorded.lock().unwrap(), events[1..]);
+    assert_eq!(
+        events,
+        vec![GameEvent::LevelLoaded, GameEvent::LandedOnPlatform]
+    );
+    assert_eq!(world.stats().landings, 1);
+}
+
+#[test]
+fn jumping_off_a_wall_publishes_a_wall_jump() {
+    let mut world = World::new(vec![wall_level()]);
+
+    // Slide down the wall while pushing into it
+    world.player_input_mut().right.press();
+    for _ in 0..10 {
+        world.step();
+    }
+    world.drain_events();
+
+    world.player_input_mut().space.press();
+    world.step();
+
+    let events = world.drain_events();
+    assert!(events.contains(&GameEvent::WallJumped));
+    assert!(!events.contains(&GameEvent::PlayerJumped));
+    assert!(world.player_transform().velocity.x < 0.0);
+    assert_eq!(world.stats().wall_jumps, 1);
+    assert_eq!(world.stats().jumps, 0);
+}
+
+#[test]
+fn reaching_the_goal_is_handled_through_events() {
+    let short_level = || {
+        let mut level = floor_level();
+        level.player_start = Vector2::new(600.0, 550.0);
+        level.goal = Vector2::new(800.0, 550.0);
+        level
+    };
+    let mut world = World::new(vec![short_level(), short_level()]);
+    world.drain_events();
+
+    world.player_input_mut().right.press();
+    let mut events = Vec::new();
+    for _ in 0..300 {
+        world.step();
+        events.extend(world.drain_events());
+        if world.level_index() == 1 {
+            break;
+        }
+    }
+
+    // The level only changes in reaction to the goal being reached
+    let goal_reached = events
+        .iter()
+        .position(|&event| event == GameEvent::GoalReached)
+        .unwrap();
+    assert_eq!(
+        events[goal_reached..],
+        [
+            GameEvent::GoalReached,
+            GameEvent::LevelCompleted,
+            GameEvent::LevelLoaded
+        ]
+    );
+    assert_eq!(world.stats().levels_completed, 1);
+}
+
+#[test]
+fn undrained_events_are_capped() {
+    let mut world = World::new(vec![floor_level()]);
+
+    for _ in 0..MAX_HANDLED_EVENTS + 10 {
+        world.kill_player();
+        world.step();
+    }
+
+    let events = world.drain_events();
+    assert_eq!(events.len(), MAX_HANDLED_EVENTS);
+    assert!(events.iter().all(|&event| event == GameEvent::PlayerDied));
+    assert_eq!(world.stats().deaths, MAX_HANDLED_EVENTS as u32 + 10);
+}
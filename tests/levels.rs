@@ -0,0 +1,140 @@
+mod common;
+
+use std::fs;
+
+use common::asset_path;
+
+use window_platformer::{
+    assets::AssetError,
+    levels::{self, Level, LEVELS_DIRECTORY},
+    movement::MovementProfiles,
+    prefabs::Prefabs,
+};
+
+fn write_level(name: &str, contents: &str) -> std::path::PathBuf {
+    let directory = std::env::temp_dir().join(format!("window-platformer-{}", name));
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("level.json");
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn shipped_levels_load() {
+    let levels = levels::load_levels(
+        &asset_path(LEVELS_DIRECTORY),
+        &Prefabs::builtin(),
+        &MovementProfiles::builtin(),
+    )
+    .unwrap();
+
+    assert!(!levels.is_empty());
+}
+
+#[test]
+fn missing_field_names_the_file_and_field() {
+    let path = write_level(
+        "missing-field",
+        r#"{ "platforms": [], "player_start": { "x": 0.0, "y": 0.0 } }"#,
+    );
+
+    let error = Level::from_file(&path).err().unwrap();
+
+    assert!(matches!(error, AssetError::Parse { .. }));
+    let message = error.to_string();
+    assert!(message.contains(&path.display().to_string()));
+    assert!(message.contains("goal"));
+}
+
+#[test]
+fn invalid_platform_size_names_the_field() {
+    let path = write_level(
+        "invalid-size",
+        r#"{
+            "platforms": [{ "position": { "x": 0.0, "y": 0.0 }, "size": { "x": 10.0, "y": 0.0 } }],
+            "player_start": { "x": 0.0, "y": 0.0 },
+            "goal": { "x": 0.0, "y": 0.0 }
+        }"#,
+    );
+
+    let error = Level::from_file(&path).err().unwrap();
+
+    assert!(error.to_string().contains("platforms[0].size"));
+}
+
+#[test]
+fn overflowing_entity_size_names_the_field() {
+    // Too big for an f32, so it parses as infinity
+    let path = write_level(
+        "infinite-size",
+        r#"{
+            "platforms": [],
+            "entities": [{ "prefab": "hazard", "position": { "x": 0.0, "y": 0.0 }, "size": { "x": 1e39, "y": 10.0 } }],
+            "player_start": { "x": 0.0, "y": 0.0 },
+            "goal": { "x": 0.0, "y": 0.0 }
+        }"#,
+    );
+
+    let error = Level::from_file(&path).err().unwrap();
+
+    assert!(
+        matches!(&error, AssetError::InvalidField { field, .. } if field == "entities[0].size"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn unknown_prefab_names_the_entity() {
+    let path = write_level(
+        "unknown-prefab",
+        r#"{
+            "platforms": [],
+            "entities": [{ "prefab": "spikes", "position": { "x": 0.0, "y": 0.0 } }],
+            "player_start": { "x": 0.0, "y": 0.0 },
+            "goal": { "x": 0.0, "y": 0.0 }
+        }"#,
+    );
+
+    let error = levels::load_levels(
+        path.parent().unwrap(),
+        &Prefabs::builtin(),
+        &MovementProfiles::builtin(),
+    )
+    .err()
+    .unwrap();
+
+    assert!(
+        matches!(&error, AssetError::InvalidField { field, .. } if field == "entities[0].prefab"),
+        "{}",
+        error
+    );
+    assert!(error.to_string().contains(&path.display().to_string()));
+}
+
+#[test]
+fn unknown_movement_profile_names_the_field() {
+    let path = write_level(
+        "unknown-movement",
+        r#"{
+            "platforms": [],
+            "movement": "slippery",
+            "player_start": { "x": 0.0, "y": 0.0 },
+            "goal": { "x": 0.0, "y": 0.0 }
+        }"#,
+    );
+
+    let error = levels::load_levels(
+        path.parent().unwrap(),
+        &Prefabs::builtin(),
+        &MovementProfiles::builtin(),
+    )
+    .err()
+    .unwrap();
+
+    assert!(
+        matches!(&error, AssetError::InvalidField { field, .. } if field == "movement"),
+        "{}",
+        error
+    );
+}
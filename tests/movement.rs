@@ -0,0 +1,172 @@
+mod common;
+
+use std::{
+    fs::{self, File},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use window_platformer::{
+    assets::AssetError,
+    levels::Level,
+    movement::{self, MovementProfile, MovementProfiles, MovementWatcher, MOVEMENT_DIRECTORY},
+    prefabs::Prefabs,
+    world::World,
+};
+
+// The shared floor level, using the named movement profile
+fn floor_level(movement: Option<&str>) -> Level {
+    Level {
+        movement: movement.map(str::to_string),
+        ..common::floor_level()
+    }
+}
+
+fn jump_height(world: &mut World) -> f32 {
+    for _ in 0..120 {
+        world.step();
+    }
+    let ground_y = world.player_transform().position.y;
+
+    world.player_input_mut().space.press();
+    let mut apex_y = ground_y;
+    for _ in 0..60 {
+        world.step();
+        apex_y = apex_y.min(world.player_transform().position.y);
+    }
+
+    ground_y - apex_y
+}
+
+#[test]
+fn shipped_profiles_load() {
+    let profiles =
+        movement::load_movement_profiles(&common::asset_path(MOVEMENT_DIRECTORY)).unwrap();
+    let builtin = MovementProfiles::builtin();
+
+    for name in ["default", "floaty", "tight"] {
+        assert_eq!(profiles.get(name), builtin.get(name));
+    }
+}
+
+#[test]
+fn invalid_profile_reports_the_field() {
+    let directory = std::env::temp_dir().join("window_platformer_invalid_movement");
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("default.json");
+    let contents = fs::read_to_string(common::asset_path(MOVEMENT_DIRECTORY).join("default.json"))
+        .unwrap()
+        .replace("\"jump_cut_divisor\": 3.0", "\"jump_cut_divisor\": 0.5");
+    fs::write(&path, contents).unwrap();
+
+    let error = MovementProfile::from_file(&path).unwrap_err();
+    assert!(
+        matches!(&error, AssetError::InvalidField { field, .. } if field == "jump_cut_divisor"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn levels_select_their_profile() {
+    let builtin = MovementProfiles::builtin();
+    let mut default_world = World::new(vec![floor_level(None)]);
+    let mut floaty_world = World::new(vec![floor_level(Some("floaty"))]);
+
+    assert_eq!(Some(default_world.movement()), builtin.get("default"));
+    assert_eq!(Some(floaty_world.movement()), builtin.get("floaty"));
+    assert_eq!(
+        floaty_world.player_transform().max_speed,
+        builtin.get("floaty").unwrap().max_speed
+    );
+
+    // Tuned in data alone, the same press jumps to a different height
+    let default_height = jump_height(&mut default_world);
+    let floaty_height = jump_height(&mut floaty_world);
+    assert!((default_height - floaty_height).abs() > 1.0);
+}
+
+#[test]
+fn custom_profiles_can_be_added() {
+    let mut profiles = MovementProfiles::builtin();
+    let mut moon = *profiles.get("default").unwrap();
+    moon.gravity /= 6.0;
+    profiles.insert("moon", moon);
+
+    let mut default_world = World::new(vec![floor_level(None)]);
+    let mut moon_world = World::with_assets(
+        vec![floor_level(Some("moon"))],
+        Prefabs::builtin(),
+        profiles,
+    );
+
+    assert!(jump_height(&mut moon_world) > jump_height(&mut default_world));
+}
+
+// Rewrites the file with a modified time that is sure to differ from the last
+fn edit(path: &Path, contents: &str, seconds_later: u64) {
+    fs::write(path, contents).unwrap();
+    File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 + seconds_later))
+        .unwrap();
+}
+
+#[test]
+fn watcher_reloads_edited_profiles() {
+    let directory = std::env::temp_dir().join("window_platformer_watched_movement");
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("default.json");
+    let contents =
+        fs::read_to_string(common::asset_path(MOVEMENT_DIRECTORY).join("default.json")).unwrap();
+    edit(&path, &contents, 0);
+
+    let mut movement_watcher = MovementWatcher::new(&directory);
+    assert!(movement_watcher.poll().is_empty());
+
+    edit(
+        &path,
+        &contents.replace("\"gravity\": 8829.0", "\"gravity\": 4000.0"),
+        1,
+    );
+    let changed = movement_watcher.poll();
+    assert_eq!(changed.len(), 1);
+    assert_eq!(changed[0].0, "default");
+    assert_eq!(changed[0].1.as_ref().unwrap().gravity, 4000.0);
+    assert!(movement_watcher.poll().is_empty());
+
+    // A broken edit is reported rather than applied
+    edit(&path, "{ \"gravity\": ", 2);
+    let changed = movement_watcher.poll();
+    assert!(matches!(changed[0].1, Err(AssetError::Parse { .. })));
+}
+
+#[test]
+fn tuning_applies_without_resetting_the_level() {
+    let mut world = World::new(vec![floor_level(None)]);
+    world.player_input_mut().right.press();
+    for _ in 0..10 {
+        world.step();
+    }
+    let position = world.player_transform().position;
+    let velocity = world.player_transform().velocity;
+
+    let mut tight = *MovementProfiles::builtin().get("tight").unwrap();
+    world.set_movement_profile("floaty", tight);
+    // Not the profile this level uses
+    assert_ne!(world.movement().gravity, tight.gravity);
+
+    tight.max_speed = 500.0;
+    world.set_movement_profile("default", tight);
+    assert_eq!(*world.movement(), tight);
+    assert_eq!(world.player_transform().max_speed, 500.0);
+    assert_eq!(world.player_transform().position, position);
+    assert_eq!(world.player_transform().velocity, velocity);
+    assert_eq!(world.level_index(), 0);
+
+    // Reloading the level keeps the tuned values
+    world.load_level(0);
+    assert_eq!(*world.movement(), tight);
+}
@@ -0,0 +1,18 @@
+use sfml::system::Vector2;
+use window_platformer::pixel_text::{self, GLYPH_HEIGHT, GLYPH_WIDTH};
+
+#[test]
+fn text_size_fits_the_longest_line() {
+    let size = pixel_text::text_size("Deaths 3\nTime 1:02.345");
+
+    // 13 glyphs with a pixel between each, and 2 lines with a pixel between
+    assert_eq!(
+        size,
+        Vector2::new(13 * GLYPH_WIDTH + 12, 2 * GLYPH_HEIGHT + 1)
+    );
+}
+
+#[test]
+fn empty_text_has_no_size() {
+    assert_eq!(pixel_text::text_size(""), Vector2::new(0, 0));
+}
@@ -0,0 +1,177 @@
+mod common;
+
+use std::fs;
+
+use sfml::system::Vector2;
+use window_platformer::{
+    assets::AssetError,
+    components::{CInput, CPickup, CRender, CTransform},
+    entities::{entity_manager::EntityManager, entity_memory_pool::EntityMemoryPool, EntityTag},
+    events::GameEvent,
+    levels::{Level, LevelEntity},
+    prefabs::{Prefabs, PREFABS_FILE},
+    world::World,
+};
+
+#[test]
+fn shipped_prefabs_load() {
+    let prefabs = Prefabs::from_file(&common::asset_path(PREFABS_FILE)).unwrap();
+
+    for name in ["player", "goal", "platform", "hazard"] {
+        assert!(prefabs.contains(name), "{}", name);
+    }
+}
+
+#[test]
+fn missing_required_prefab_is_an_error() {
+    let directory = std::env::temp_dir().join("window-platformer-missing-prefab");
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("prefabs.json");
+    fs::write(
+        &path,
+        r#"{ "player": { "tag": "Player", "size": { "x": 25.0, "y": 50.0 } } }"#,
+    )
+    .unwrap();
+
+    let error = Prefabs::from_file(&path).err().unwrap();
+
+    assert!(matches!(error, AssetError::InvalidField { .. }));
+    assert!(error.to_string().contains("`goal`"));
+}
+
+#[test]
+fn required_prefabs_are_checked() {
+    let directory = std::env::temp_dir().join("window-platformer-invalid-prefab");
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("prefabs.json");
+    let shipped = fs::read_to_string(common::asset_path(PREFABS_FILE)).unwrap();
+
+    let edits = [
+        ("\"tag\": \"Goal\"", "\"tag\": \"Hazard\"", "goal.tag"),
+        ("\"input\": true", "\"input\": false", "player.input"),
+        (
+            "\"size\": { \"x\": 100.0, \"y\": 50.0 }",
+            "\"size\": { \"x\": 1e39, \"y\": 50.0 }",
+            "platform.size",
+        ),
+    ];
+    for (from, to, field) in edits {
+        fs::write(&path, shipped.replace(from, to)).unwrap();
+
+        let error = Prefabs::from_file(&path).err().unwrap();
+        assert!(
+            matches!(&error, AssetError::InvalidField { field: found, .. } if found == field),
+            "{}",
+            error
+        );
+    }
+}
+
+#[test]
+fn prefab_spawns_an_entity_with_its_components() {
+    let prefabs = Prefabs::builtin();
+    let mut entity_memory_pool = EntityMemoryPool::new(4);
+    let mut entity_manager = EntityManager::new();
+
+    let player = prefabs.spawn(
+        "player",
+        Vector2::new(100.0, 200.0),
+        None,
+        &mut entity_manager,
+        &mut entity_memory_pool,
+    );
+    let platform = prefabs.spawn(
+        "platform",
+        Vector2::new(300.0, 400.0),
+        Some(Vector2::new(500.0, 20.0)),
+        &mut entity_manager,
+        &mut entity_memory_pool,
+    );
+
+    assert_eq!(entity_memory_pool.get_tag(player), Some(&EntityTag::Player));
+    assert!(player.has_component::<CInput>(&entity_memory_pool));
+    assert!(player.has_component::<CRender>(&entity_memory_pool));
+    let player_transform = player
+        .get_component::<CTransform>(&entity_memory_pool)
+        .unwrap();
+    assert_eq!(player_transform.position, Vector2::new(100.0, 200.0));
+
+    assert!(!platform.has_component::<CInput>(&entity_memory_pool));
+    assert_eq!(
+        platform
+            .get_component::<CTransform>(&entity_memory_pool)
+            .unwrap()
+            .half_size,
+        Vector2::new(250.0, 10.0)
+    );
+}
+
+// The shared floor level with one `prefab` in the player's path as they fall
+// to the floor
+fn falling_onto(prefab: &str) -> Level {
+    let mut level = common::floor_level();
+    level.player_start = Vector2::new(960.0, 300.0);
+    level.entities.push(LevelEntity {
+        prefab: prefab.to_string(),
+        position: Vector2::new(960.0, 500.0),
+        size: None,
+    });
+    level
+}
+
+#[test]
+fn touching_a_hazard_kills_the_player() {
+    let mut world = World::new(vec![falling_onto("hazard")]);
+
+    assert_eq!(
+        world
+            .entity_manager()
+            .get_entity_count_by_tag(EntityTag::Hazard),
+        1
+    );
+
+    for _ in 0..60 {
+        world.step();
+        if world.deaths() > 0 {
+            break;
+        }
+    }
+
+    assert_eq!(world.deaths(), 1);
+    assert!(world.drain_events().contains(&GameEvent::PlayerDied));
+    assert_eq!(
+        world.player_transform().position,
+        Vector2::new(960.0, 300.0)
+    );
+}
+
+#[test]
+fn collecting_a_pickup_grants_an_air_jump() {
+    let mut world = World::new(vec![falling_onto("double_jump")]);
+    let pickup = world
+        .entity_manager()
+        .get_entities_by_tag(EntityTag::Pickup)
+        .unwrap()[0];
+    assert!(pickup.has_component::<CPickup>(world.entity_memory_pool()));
+    assert_eq!(world.max_air_jumps(), 0);
+
+    for _ in 0..120 {
+        world.step();
+    }
+
+    assert_eq!(world.max_air_jumps(), 1);
+    assert_eq!(world.air_jumps(), 1);
+    assert!(world
+        .drain_events()
+        .contains(&GameEvent::PickupCollected(pickup)));
+    assert_eq!(
+        world
+            .entity_manager()
+            .get_entity_count_by_tag(EntityTag::Pickup),
+        0
+    );
+
+    // Reloading the level takes the pickup's jump away again
+    world.load_level(0);
+    assert_eq!(world.max_air_jumps(), 0);
+}
@@ -0,0 +1,476 @@
+mod common;
+
+use common::{floor_level, platform, step_frames, wall_level};
+use sfml::system::Vector2;
+use window_platformer::{
+    events::GameEvent,
+    levels::Level,
+    world::{GameState, World},
+};
+
+#[test]
+fn player_lands_on_platform() {
+    let mut world = World::new(vec![floor_level()]);
+
+    step_frames(&mut world, 120);
+
+    // Resting on top of the floor, 575 minus half the player's height, without
+    // jittering from frame to frame
+    for _ in 0..60 {
+        world.step();
+
+        let player_transform = world.player_transform();
+        assert!(player_transform.grounded);
+        assert_eq!(player_transform.position.y, 550.0);
+        assert_eq!(player_transform.velocity.y, 0.0);
+    }
+}
+
+#[test]
+fn player_jumps_from_the_ground() {
+    let mut world = World::new(vec![floor_level()]);
+    step_frames(&mut world, 120);
+    world.drain_events();
+
+    world.player_input_mut().space.press();
+    world.step();
+
+    assert!(world.drain_events().contains(&GameEvent::PlayerJumped));
+    assert!(world.player_transform().velocity.y < 0.0);
+    assert!(!world.player_transform().grounded);
+}
+
+#[test]
+fn player_dies_and_respawns_after_falling_off() {
+    let mut level = floor_level();
+    level.platforms.clear();
+    let mut world = World::new(vec![level]);
+
+    step_frames(&mut world, 120);
+
+    assert!(world.deaths() >= 1);
+    assert!(world.drain_events().contains(&GameEvent::PlayerDied));
+    assert!(world.player_transform().position.y < 1080.0);
+}
+
+#[test]
+fn reaching_the_goal_advances_and_completes_the_campaign() {
+    let mut first_level = floor_level();
+    first_level.player_start = Vector2::new(600.0, 550.0);
+    first_level.goal = Vector2::new(800.0, 550.0);
+    let mut second_level = floor_level();
+    second_level.player_start = Vector2::new(600.0, 550.0);
+    second_level.goal = Vector2::new(800.0, 550.0);
+
+    let mut world = World::new(vec![first_level, second_level]);
+
+    world.player_input_mut().right.press();
+    for _ in 0..300 {
+        world.step();
+        if world.level_index() == 1 {
+            break;
+        }
+    }
+    assert_eq!(world.level_index(), 1);
+    assert_eq!(world.game_state(), GameState::Playing);
+
+    for _ in 0..300 {
+        world.step();
+        if world.game_state() == GameState::CampaignComplete {
+            break;
+        }
+    }
+    assert_eq!(world.game_state(), GameState::CampaignComplete);
+    assert!(world.drain_events().contains(&GameEvent::CampaignCompleted));
+
+    world.restart_campaign();
+    assert_eq!(world.game_state(), GameState::Playing);
+    assert_eq!(world.level_index(), 0);
+    assert_eq!(world.deaths(), 0);
+}
+
+#[test]
+fn jump_height_is_measured_in_simulated_time() {
+    let mut world = World::new(vec![floor_level()]);
+    step_frames(&mut world, 120);
+    let ground_y = world.player_transform().position.y;
+
+    world.player_input_mut().space.press();
+    let mut apex_y = ground_y;
+    for _ in 0..60 {
+        world.step();
+        apex_y = apex_y.min(world.player_transform().position.y);
+    }
+
+    // A 1800 px/s jump against 8829 px/s^2 of gravity, integrated in 60 Hz
+    // ticks, peaks a little under the continuous v^2 / 2g of ~183 px
+    let jump_height = ground_y - apex_y;
+    assert!((165.0..=175.0).contains(&jump_height), "{}", jump_height);
+}
+
+#[test]
+fn campaign_time_does_not_drift() {
+    let mut world = World::new(vec![floor_level()]);
+
+    // A minute of ticks
+    step_frames(&mut world, 3600);
+
+    let error = world.campaign_time().as_secs_f64() - 60.0;
+    assert!(error.abs() < 1e-4, "{}", error);
+}
+
+#[test]
+fn worlds_run_independently_in_parallel() {
+    let run = |player_x: f32| {
+        let mut level = floor_level();
+        level.player_start.x = player_x;
+        let mut world = World::new(vec![level]);
+
+        world.player_input_mut().right.press();
+        step_frames(&mut world, 20);
+
+        world.player_transform().position
+    };
+
+    let expected: Vec<_> = [700.0, 800.0, 900.0, 1000.0].into_iter().map(run).collect();
+
+    let handles: Vec<_> = [700.0, 800.0, 900.0, 1000.0]
+        .into_iter()
+        .map(|player_x| std::thread::spawn(move || run(player_x)))
+        .collect();
+    let positions: Vec<_> = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect();
+
+    assert_eq!(positions, expected);
+    // Each world moved only its own player
+    assert!(positions
+        .windows(2)
+        .all(|pair| (pair[1].x - pair[0].x - 100.0).abs() < 0.01));
+}
+
+#[test]
+fn pushing_into_a_wall_caps_the_fall_speed() {
+    let mut world = World::new(vec![wall_level()]);
+    let wall_slide_speed = world.movement().wall_slide_speed;
+    world.drain_events();
+
+    world.player_input_mut().right.press();
+    step_frames(&mut world, 30);
+
+    assert!(world.is_wall_sliding());
+    assert!((world.player_transform().velocity.y - wall_slide_speed).abs() < 0.01);
+    assert!(world.drain_events().contains(&GameEvent::WallSlideStarted));
+
+    // Letting go falls at the full rate again
+    world.player_input_mut().right.release();
+    world.step();
+    assert!(!world.is_wall_sliding());
+    assert!(world.drain_events().contains(&GameEvent::WallSlideEnded));
+    step_frames(&mut world, 5);
+    assert!(world.player_transform().velocity.y > wall_slide_speed);
+}
+
+#[test]
+fn falling_beside_a_wall_without_pushing_is_not_a_slide() {
+    let mut world = World::new(vec![wall_level()]);
+    let wall_slide_speed = world.movement().wall_slide_speed;
+
+    step_frames(&mut world, 30);
+
+    assert!(!world.is_wall_sliding());
+    assert!(world.player_transform().velocity.y > wall_slide_speed);
+}
+
+#[test]
+fn respawning_forgets_the_wall_and_floor() {
+    // The wall level, plus a floor away from the wall to respawn on
+    let mut level = wall_level();
+    level.platforms.push(platform(400.0, 600.0, 400.0, 50.0));
+    level.player_start = Vector2::new(400.0, 550.0);
+    let mut world = World::new(vec![level]);
+    world.player_transform_mut().position = Vector2::new(1050.0, 200.0);
+
+    world.player_input_mut().right.press();
+    step_frames(&mut world, 10);
+    assert!(world.is_wall_sliding());
+    world.drain_events();
+
+    // Respawned at the end of a step, as touching a hazard does, while still
+    // holding toward the wall
+    world.kill_player();
+    world.s_events();
+    step_frames(&mut world, 2);
+    let events = world.drain_events();
+    assert!(!events.contains(&GameEvent::WallSlideStarted));
+    assert!(!world.is_wall_sliding());
+    assert!(events.contains(&GameEvent::LandedOnPlatform));
+
+    // Dying while standing still lands again at the spawn point
+    world.player_input_mut().right.release();
+    step_frames(&mut world, 30);
+    world.drain_events();
+    world.kill_player();
+    world.s_events();
+    step_frames(&mut world, 2);
+    assert!(world.drain_events().contains(&GameEvent::LandedOnPlatform));
+}
+
+#[test]
+fn wall_jumping_ends_a_slide() {
+    let mut world = World::new(vec![wall_level()]);
+    world.player_input_mut().right.press();
+    step_frames(&mut world, 30);
+    world.drain_events();
+
+    world.player_input_mut().space.press();
+    world.step();
+
+    let events = world.drain_events();
+    assert!(events.contains(&GameEvent::WallJumped));
+    assert!(events.contains(&GameEvent::WallSlideEnded));
+    assert!(!world.is_wall_sliding());
+    assert!(world.player_transform().velocity.y < 0.0);
+}
+
+fn dash_level() -> Level {
+    let mut level = floor_level();
+    level.dash = true;
+    level.player_start.y = 100.0;
+    level
+}
+
+#[test]
+fn dashing_is_off_unless_the_level_allows_it() {
+    let mut world = World::new(vec![floor_level()]);
+    step_frames(&mut world, 5);
+    world.drain_events();
+
+    world.player_input_mut().dash.press();
+    world.step();
+
+    assert!(!world.is_dashing());
+    assert!(!world.drain_events().contains(&GameEvent::PlayerDashed));
+}
+
+#[test]
+fn dash_ignores_gravity_for_its_duration() {
+    let mut world = World::new(vec![dash_level()]);
+    let movement = *world.movement();
+    step_frames(&mut world, 5);
+    world.drain_events();
+
+    world.player_input_mut().right.press();
+    world.player_input_mut().dash.press();
+    world.step();
+    assert!(world.is_dashing());
+    assert!(world.drain_events().contains(&GameEvent::PlayerDashed));
+    assert_eq!(world.dash_charges(), movement.air_dashes - 1);
+
+    let dash_y = world.player_transform().position.y;
+    let mut frames = 1;
+    while world.is_dashing() {
+        assert_eq!(world.player_transform().position.y, dash_y);
+        assert_eq!(world.player_transform().velocity.x, movement.dash_speed);
+        world.step();
+        frames += 1;
+    }
+    assert_eq!(frames, (movement.dash_duration * 60.0).round() as u32);
+
+    // Out of the dash at running speed, and falling again
+    assert_eq!(
+        world.player_transform().velocity.x,
+        world.player_transform().max_speed
+    );
+    world.step();
+    assert!(world.player_transform().position.y > dash_y);
+}
+
+#[test]
+fn air_dashes_refill_on_landing() {
+    let mut world = World::new(vec![dash_level()]);
+    let mut movement = *world.movement();
+    movement.dash_cooldown = 0.0;
+    movement.dash_duration = 2.0 / 60.0;
+    world.set_movement_profile("default", movement);
+    step_frames(&mut world, 2);
+
+    for _ in 0..movement.air_dashes {
+        world.player_input_mut().dash.press();
+        step_frames(&mut world, 3);
+    }
+    assert_eq!(world.dash_charges(), 0);
+    world.drain_events();
+
+    // No charges left until the player is back on the ground
+    world.player_input_mut().dash.press();
+    world.step();
+    assert!(!world.is_dashing());
+    assert!(!world.drain_events().contains(&GameEvent::PlayerDashed));
+
+    step_frames(&mut world, 120);
+    assert!(world.player_transform().grounded);
+    assert_eq!(world.dash_charges(), movement.air_dashes);
+}
+
+#[test]
+fn ground_dashes_do_not_need_a_charge() {
+    let mut level = floor_level();
+    level.dash = true;
+    let mut world = World::new(vec![level]);
+    let mut movement = *world.movement();
+    movement.air_dashes = 0;
+    world.set_movement_profile("default", movement);
+    step_frames(&mut world, 120);
+
+    world.player_input_mut().dash.press();
+    world.step();
+    assert!(world.is_dashing());
+    step_frames(&mut world, 60);
+
+    // Off the ground there is none to spend
+    world.player_input_mut().space.press();
+    step_frames(&mut world, 5);
+    world.player_input_mut().dash.press();
+    world.step();
+    assert!(!world.is_dashing());
+}
+
+#[test]
+fn dashing_along_the_ground_stays_grounded() {
+    let mut level = floor_level();
+    level.dash = true;
+    let mut world = World::new(vec![level]);
+    step_frames(&mut world, 120);
+    world.drain_events();
+
+    world.player_input_mut().right.press();
+    world.player_input_mut().dash.press();
+    world.step();
+    while world.is_dashing() {
+        assert!(world.player_transform().grounded);
+        world.step();
+    }
+    step_frames(&mut world, 10);
+
+    let events = world.drain_events();
+    assert!(events.contains(&GameEvent::PlayerDashed));
+    assert!(!events.contains(&GameEvent::LandedOnPlatform));
+    assert_eq!(world.stats().landings, 1);
+}
+
+#[test]
+fn a_jump_pressed_during_a_dash_happens_when_it_ends() {
+    let mut level = floor_level();
+    level.dash = true;
+    let mut world = World::new(vec![level]);
+    step_frames(&mut world, 120);
+
+    world.player_input_mut().dash.press();
+    world.step();
+    world.player_input_mut().space.press();
+    while world.is_dashing() {
+        world.step();
+        assert!(!world.drain_events().contains(&GameEvent::PlayerJumped));
+    }
+    world.step();
+
+    assert!(world.drain_events().contains(&GameEvent::PlayerJumped));
+    assert!(world.player_transform().velocity.y < 0.0);
+}
+
+fn jump_events(events: &[GameEvent]) -> usize {
+    events
+        .iter()
+        .filter(|&&event| event == GameEvent::PlayerJumped || event == GameEvent::AirJumped)
+        .count()
+}
+
+#[test]
+fn air_jumps_refill_on_landing() {
+    let mut level = floor_level();
+    level.air_jumps = 1;
+    let mut world = World::new(vec![level]);
+    step_frames(&mut world, 120);
+    assert_eq!(world.air_jumps(), 1);
+
+    world.player_input_mut().space.press();
+    step_frames(&mut world, 10);
+    world.player_input_mut().space.release();
+    world.step();
+    world.drain_events();
+
+    world.player_input_mut().space.press();
+    world.step();
+    world.player_input_mut().space.release();
+    assert!(world.drain_events().contains(&GameEvent::AirJumped));
+    assert!(world.player_transform().velocity.y < 0.0);
+    assert_eq!(world.air_jumps(), 0);
+
+    // Out of air jumps the press only waits in the buffer
+    step_frames(&mut world, 5);
+    world.player_input_mut().space.press();
+    world.step();
+    world.player_input_mut().space.release();
+    assert!(!world.drain_events().contains(&GameEvent::AirJumped));
+
+    step_frames(&mut world, 120);
+    assert!(world.player_transform().grounded);
+    assert_eq!(world.air_jumps(), 1);
+}
+
+#[test]
+fn coyote_jump_does_not_spend_an_air_jump() {
+    // Walk off the right edge of the floor, at x = 1460
+    let mut level = floor_level();
+    level.air_jumps = 1;
+    level.player_start = Vector2::new(1400.0, 550.0);
+    let mut world = World::new(vec![level]);
+    step_frames(&mut world, 5);
+
+    world.player_input_mut().right.press();
+    while world.player_transform().grounded {
+        world.step();
+    }
+    world.drain_events();
+
+    world.player_input_mut().space.press();
+    world.step();
+
+    let events = world.drain_events();
+    assert!(events.contains(&GameEvent::PlayerJumped));
+    assert_eq!(jump_events(&events), 1);
+    assert_eq!(world.air_jumps(), 1);
+}
+
+#[test]
+fn a_press_just_before_landing_jumps_once() {
+    for air_jumps in [0, 1] {
+        let mut level = floor_level();
+        level.air_jumps = air_jumps;
+        let mut world = World::new(vec![level]);
+
+        // A couple of frames above the floor
+        while world.player_transform().position.y < 530.0 {
+            world.step();
+        }
+        world.drain_events();
+
+        world.player_input_mut().space.press();
+        let mut events = Vec::new();
+        for _ in 0..30 {
+            world.step();
+            events.extend(world.drain_events());
+        }
+
+        // Spent in the air if an air jump is left, otherwise buffered until landing
+        assert_eq!(jump_events(&events), 1, "{} air jumps", air_jumps);
+        let expected = if air_jumps > 0 {
+            GameEvent::AirJumped
+        } else {
+            GameEvent::PlayerJumped
+        };
+        assert!(events.contains(&expected));
+    }
+}